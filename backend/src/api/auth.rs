@@ -17,6 +17,7 @@ use tracing::warn;
 
 use crate::{core::AppState, core::error::AppError, core::database::get_or_create_jwt_secret};
 use crate::core::error::codes::ErrorCode;
+use crate::core::permissions::{is_granted, Permission};
 use crate::api::SuccessResponse;
 
 // ============= Rate Limiting =============
@@ -344,31 +345,12 @@ pub struct AuthUser {
     pub role: String,
     pub permissions: Vec<String>,
     pub accent_color: Option<String>,
+    pub allocated_servers: Vec<String>,
 }
 
-#[async_trait]
-impl FromRequestParts<AppState> for AuthUser {
-    type Rejection = AppError;
-
-    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
-        let auth_header = parts.headers
-            .get("Authorization")
-            .and_then(|h| h.to_str().ok())
-            .ok_or_else(|| {
-                warn!("Auth failed: Missing Authorization header");
-                AppError::Unauthorized("auth.missing_auth_header".into())
-                    .with_code(ErrorCode::AuthMissingHeader)
-            })?;
-
-        let token = auth_header
-            .strip_prefix("Bearer ")
-            .ok_or_else(|| {
-                warn!("Auth failed: Invalid Authorization header format");
-                AppError::Unauthorized("auth.invalid_auth_header".into())
-                    .with_code(ErrorCode::AuthInvalidHeader)
-            })?;
-
-        // Use state directly passed by Axum
+impl AuthUser {
+    /// Decode a JWT and load the permissions granted by the user's role
+    pub async fn from_token(state: &AppState, token: &str) -> Result<Self, AppError> {
         let secret = get_jwt_secret(state).await?;
         
         let token_data = jsonwebtoken::decode::<Claims>(
@@ -399,14 +381,90 @@ impl FromRequestParts<AppState> for AuthUser {
             vec![]
         };
 
+        // Fetch the servers this user may access (ignored for admins)
+        let allocated: Option<(Option<String>,)> = sqlx::query_as(
+            "SELECT allocated_servers FROM users WHERE id = ?"
+        )
+        .bind(&token_data.claims.sub)
+        .fetch_optional(&state.pool)
+        .await
+        .unwrap_or(None);
+
+        let allocated_servers: Vec<String> = allocated
+            .and_then(|(s,)| s)
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+
         Ok(AuthUser {
             id: token_data.claims.sub,
             username: token_data.claims.username,
             role: token_data.claims.role,
             permissions,
             accent_color: token_data.claims.accent_color,
+            allocated_servers,
         })
     }
+
+    /// Admins (role or global wildcard) are not restricted to their allocated servers
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin" || self.permissions.iter().any(|p| p == "*")
+    }
+
+    pub fn can_access_server(&self, server_id: &str) -> bool {
+        self.is_admin() || self.allocated_servers.iter().any(|id| id == server_id)
+    }
+
+    /// Reject the request unless `server_id` is in the user's allocation
+    pub fn require_server(&self, server_id: &str) -> Result<(), AppError> {
+        if self.can_access_server(server_id) {
+            return Ok(());
+        }
+
+        warn!(user = %self.username, server_id = server_id, "Server access denied");
+        Err(AppError::Forbidden("servers.access_denied".into())
+            .with_code(ErrorCode::ServerAccessDenied))
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        is_granted(&self.permissions, permission)
+    }
+
+    /// Reject the request unless the user's role grants `permission`
+    pub fn require(&self, permission: Permission) -> Result<(), AppError> {
+        if self.has_permission(permission) {
+            return Ok(());
+        }
+
+        warn!(user = %self.username, permission = %permission, "Permission denied");
+        Err(AppError::Forbidden("auth.permission_denied".into())
+            .with_code(ErrorCode::AuthPermissionDenied))
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let auth_header = parts.headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .ok_or_else(|| {
+                warn!("Auth failed: Missing Authorization header");
+                AppError::Unauthorized("auth.missing_auth_header".into())
+                    .with_code(ErrorCode::AuthMissingHeader)
+            })?;
+
+        let token = auth_header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| {
+                warn!("Auth failed: Invalid Authorization header format");
+                AppError::Unauthorized("auth.invalid_auth_header".into())
+                    .with_code(ErrorCode::AuthInvalidHeader)
+            })?;
+
+        AuthUser::from_token(state, token).await
+    }
 }
 
 
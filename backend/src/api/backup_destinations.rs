@@ -0,0 +1,197 @@
+use axum::{
+    routing::{get, post},
+    extract::{State, Path},
+    Json, Router,
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::Utc;
+
+use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
+use crate::api::SuccessResponse;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::system::replication;
+use crate::services::system::storage::DestinationConfig;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_destinations).post(create_destination))
+        .route("/:id", get(get_destination).put(update_destination).delete(delete_destination))
+        .route("/:id/test", post(test_destination))
+}
+
+#[derive(Debug, FromRow)]
+struct DestinationRow {
+    id: String,
+    name: String,
+    config: String,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DestinationResponse {
+    pub id: String,
+    pub name: String,
+    /// Passwords and secret keys are never returned
+    pub config: DestinationConfig,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DestinationRequest {
+    pub name: String,
+    pub config: DestinationConfig,
+}
+
+impl DestinationRow {
+    fn into_response(self) -> Result<DestinationResponse, AppError> {
+        let config: DestinationConfig = serde_json::from_str(&self.config)
+            .map_err(|e| AppError::Internal(format!("Invalid destination {}: {e}", self.id)))?;
+        Ok(DestinationResponse {
+            id: self.id,
+            name: self.name,
+            config: config.redacted(),
+            created_at: self.created_at,
+        })
+    }
+}
+
+async fn find_destination(state: &AppState, id: &str) -> Result<DestinationRow, AppError> {
+    sqlx::query_as("SELECT * FROM backup_destinations WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("backups.destination_not_found".into())
+            .with_code(ErrorCode::BackupDestinationNotFound))
+}
+
+fn config_json(config: &DestinationConfig) -> Result<String, AppError> {
+    serde_json::to_string(config).map_err(|e| AppError::Internal(format!("Failed to serialize destination: {e}")))
+}
+
+async fn list_destinations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<DestinationResponse>>, AppError> {
+    // Destinations are also listed when editing a server's replication rules
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    let rows: Vec<DestinationRow> = sqlx::query_as("SELECT * FROM backup_destinations ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+
+    let responses = rows.into_iter()
+        .map(DestinationRow::into_response)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(responses))
+}
+
+async fn get_destination(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<DestinationResponse>, AppError> {
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    Ok(Json(find_destination(&state, &id).await?.into_response()?))
+}
+
+async fn create_destination(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<DestinationRequest>,
+) -> Result<(StatusCode, Json<DestinationResponse>), AppError> {
+    auth.require(Permission::SettingsManage)?;
+    body.config.validate()?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query("INSERT INTO backup_destinations (id, name, config, created_at) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&body.name)
+        .bind(config_json(&body.config)?)
+        .bind(&now)
+        .execute(&state.pool)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(DestinationResponse {
+        id,
+        name: body.name,
+        config: body.config.redacted(),
+        created_at: now,
+    })))
+}
+
+async fn update_destination(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Json(body): Json<DestinationRequest>,
+) -> Result<Json<DestinationResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    let previous = replication::load_destination(&state.pool, &id).await?;
+    let mut config = body.config;
+    config.keep_secrets_from(&previous);
+    config.validate()?;
+
+    sqlx::query("UPDATE backup_destinations SET name = ?, config = ? WHERE id = ?")
+        .bind(&body.name)
+        .bind(config_json(&config)?)
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(Json(find_destination(&state, &id).await?.into_response()?))
+}
+
+/// Copies already on the destination are left there; only the panel forgets about them
+async fn delete_destination(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    sqlx::query("DELETE FROM backup_replicas WHERE destination_id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM backup_destinations WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("backups.destination_not_found".into())
+            .with_code(ErrorCode::BackupDestinationNotFound));
+    }
+
+    Ok(SuccessResponse::ok())
+}
+
+/// Upload and delete a probe file to check the destination's settings
+async fn test_destination(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    let config = replication::load_destination(&state.pool, &id).await?;
+    replication::test_destination(&config).await?;
+
+    Ok(SuccessResponse::with_message("Destination reachable"))
+}
@@ -1,25 +1,42 @@
 use axum::{
-    routing::{get, post},
-    extract::{Path, Query, State},
+    routing::{delete, get, post},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, Request, State},
+    body::Body,
+    response::Response,
     Json, Router,
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use tokio::io::AsyncWriteExt;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 use uuid::Uuid;
-use tokio::fs;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
 use crate::core::error::codes::ErrorCode;
+use crate::services::system::{backup, browse, chunk_store, integrity};
+use crate::services::system::replication::{self, ReplicaRow};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_backups).post(create_backup))
         .route("/:id", get(get_backup).delete(delete_backup))
+        .route("/import", post(import_backup).layer(DefaultBodyLimit::disable()))
+        .route("/:id/download", get(download_backup))
+        .route("/:id/entries", get(list_entries))
+        .route("/:id/file", get(read_file))
+        .route("/:id/diff", get(diff_backup))
         .route("/:id/restore", post(restore_backup))
+        .route("/:id/verify", post(verify_backup))
+        .route("/replicas", get(list_replicas))
+        .route("/replicas/:id", delete(delete_replica))
+        .route("/replicas/:id/fetch", post(fetch_replica))
 }
 
 #[derive(Debug, Serialize)]
@@ -28,12 +45,30 @@ pub struct BackupResponse {
     pub server_id: String,
     pub filename: String,
     pub size_bytes: i64,
+    pub kind: String,
+    /// SHA-256 of the archive or manifest
+    pub checksum: Option<String>,
+    /// "ok" or "corrupted"; unset until the backup is first verified
+    pub verify_status: Option<String>,
+    pub verify_error: Option<String>,
+    pub verified_at: Option<String>,
+    pub corrupted: bool,
+    /// "manual", "scheduled", "import", "replica" or "pre_restore"; only scheduled backups are pruned
+    pub origin: String,
     pub created_at: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateBackupRequest {
     pub server_id: String,
+    /// "archive" or "incremental"; defaults to the server's backup mode
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RestoreBackupRequest {
+    /// Relative path to restore on its own (file or directory); the whole backup when omitted
+    pub path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,29 +76,80 @@ struct ListBackupsQuery {
     server_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ImportBackupQuery {
+    server_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntriesQuery {
+    /// Only list this directory and what lies below it
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileQuery {
+    path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffQuery {
+    /// Id of the backup to compare with, or "live" for the server's working directory
+    against: String,
+}
+
 #[derive(Debug, FromRow)]
 struct BackupRow {
     id: String,
     server_id: String,
     filename: String,
     size_bytes: i64,
+    kind: String,
+    checksum: Option<String>,
+    verify_status: Option<String>,
+    verify_error: Option<String>,
+    verified_at: Option<String>,
+    origin: String,
     created_at: String,
 }
 
+impl From<BackupRow> for BackupResponse {
+    fn from(b: BackupRow) -> Self {
+        Self {
+            corrupted: b.verify_status.as_deref() == Some(integrity::STATUS_CORRUPTED),
+            id: b.id,
+            server_id: b.server_id,
+            filename: b.filename,
+            size_bytes: b.size_bytes,
+            kind: b.kind,
+            checksum: b.checksum,
+            verify_status: b.verify_status,
+            verify_error: b.verify_error,
+            verified_at: b.verified_at,
+            origin: b.origin,
+            created_at: b.created_at,
+        }
+    }
+}
+
 async fn list_backups(
     State(state): State<AppState>,
+    auth: AuthUser,
     Query(query): Query<ListBackupsQuery>,
 ) -> Result<Json<Vec<BackupResponse>>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+
     let backups: Vec<BackupRow> = if let Some(server_id) = &query.server_id {
+        auth.require_server(server_id)?;
         sqlx::query_as(
-            "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE server_id = ? ORDER BY created_at DESC"
+            "SELECT * FROM backups WHERE server_id = ? ORDER BY created_at DESC"
         )
         .bind(server_id)
         .fetch_all(&state.pool)
         .await?
     } else {
         sqlx::query_as(
-            "SELECT id, server_id, filename, size_bytes, created_at FROM backups ORDER BY created_at DESC"
+            "SELECT * FROM backups ORDER BY created_at DESC"
         )
         .fetch_all(&state.pool)
         .await?
@@ -71,13 +157,8 @@ async fn list_backups(
 
     let responses: Vec<BackupResponse> = backups
         .into_iter()
-        .map(|b| BackupResponse {
-            id: b.id,
-            server_id: b.server_id,
-            filename: b.filename,
-            size_bytes: b.size_bytes,
-            created_at: b.created_at,
-        })
+        .filter(|b| auth.can_access_server(&b.server_id))
+        .map(BackupResponse::from)
         .collect();
 
     Ok(Json(responses))
@@ -85,57 +166,56 @@ async fn list_backups(
 
 async fn create_backup(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateBackupRequest>,
 ) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
-    let server: (String, String) = sqlx::query_as("SELECT name, working_dir FROM servers WHERE id = ?")
+    auth.require(Permission::ServerBackupsCreate)?;
+    auth.require_server(&body.server_id)?;
+
+    let server: (String, String, String, Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT name, working_dir, backup_mode, backup_include, backup_exclude FROM servers WHERE id = ?"
+    )
         .bind(&body.server_id)
         .fetch_optional(&state.pool)
         .await?
         .ok_or_else(|| AppError::NotFound("Server not found".into()).with_code(ErrorCode::ServerNotFound))?;
 
-    let id = Uuid::new_v4().to_string();
-    let now = Utc::now();
-    let filename = format!(
-        "backup_{}_{}.tar.gz",
-        body.server_id,
-        now.format("%Y%m%d_%H%M%S")
-    );
-
-    let backups_dir = std::path::Path::new("backups");
-    if !backups_dir.exists() {
-        fs::create_dir_all(backups_dir).await?;
+    let (server_name, working_dir, backup_mode, backup_include, backup_exclude) = server;
+    let kind = body.kind.clone().unwrap_or(backup_mode);
+    if !backup::is_valid_kind(&kind) {
+        return Err(AppError::BadRequest("backups.invalid_mode".into()).with_code(ErrorCode::BackupInvalidMode));
     }
 
-    let backup_path = backups_dir.join(&filename);
-    let working_dir = server.1;
-    let server_name = server.0;
+    let filter = backup::BackupFilter::from_columns(backup_include.as_deref(), backup_exclude.as_deref())?;
+    let id = Uuid::new_v4().to_string();
 
-    // If server is running, try to send a save command if supported (for Hytale, we can send /save-all if it exists)
-    if state.process_manager.is_running(&body.server_id) {
-        let _ = state.process_manager.send_command(&body.server_id, "/save-all").await;
-        // Give it a moment to save
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-    }
-    
-    // Call service
-    let size_bytes = crate::services::system::backup::create_archive(working_dir, backup_path.to_string_lossy().to_string())
-        .await
-        .map_err(|e| AppError::Internal(format!("Backup failed: {e}"))
-            .with_code(ErrorCode::BackupCreateFailed))?;
+    // A running server flushes its world first and does not write to it while it is archived
+    let archive = async {
+        backup::create_backup(&body.server_id, working_dir, "backup", &kind, filter)
+            .await
+            .map_err(|e| AppError::Internal(format!("Backup failed: {e}"))
+                .with_code(ErrorCode::BackupCreateFailed))
+    };
+    let created = state.process_manager.with_saving_paused(&body.server_id, archive).await?;
 
-    let created_at = now.to_rfc3339();
+    let created_at = Utc::now().to_rfc3339();
 
     sqlx::query(
-        "INSERT INTO backups (id, server_id, filename, size_bytes, created_at) VALUES (?, ?, ?, ?, ?)",
+        "INSERT INTO backups (id, server_id, filename, size_bytes, kind, checksum, origin, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(&id)
     .bind(&body.server_id)
-    .bind(&filename)
-    .bind(size_bytes as i64)
+    .bind(&created.filename)
+    .bind(created.size as i64)
+    .bind(&kind)
+    .bind(&created.checksum)
+    .bind(backup::ORIGIN_MANUAL)
     .bind(&created_at)
     .execute(&state.pool)
     .await?;
 
+    replication::spawn_replication(state.pool.clone(), body.server_id.clone(), created.filename.clone(), kind.clone());
+
     // Discord notification
     let pool_clone = state.pool.clone();
     tokio::spawn(async move {
@@ -149,96 +229,423 @@ async fn create_backup(
         ).await;
     });
 
-    Ok((StatusCode::CREATED, Json(BackupResponse {
-        id,
-        server_id: body.server_id.clone(),
-        filename,
-        size_bytes: size_bytes as i64,
-        created_at,
-    })))
+    created_response(&state, &id).await
 }
 
 async fn get_backup(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<BackupResponse>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+
     let backup: BackupRow = sqlx::query_as(
-        "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE id = ?",
+        "SELECT * FROM backups WHERE id = ?",
     )
     .bind(&id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
 
-    Ok(Json(BackupResponse {
-        id: backup.id,
-        server_id: backup.server_id,
-        filename: backup.filename,
-        size_bytes: backup.size_bytes,
-        created_at: backup.created_at,
-    }))
+    auth.require_server(&backup.server_id)?;
+
+    Ok(Json(backup.into()))
 }
 
 async fn delete_backup(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    let backup: Option<(String,)> = sqlx::query_as("SELECT filename FROM backups WHERE id = ?")
+    auth.require(Permission::ServerBackupsDelete)?;
+
+    let (server_id, filename, kind): (String, String, String) = sqlx::query_as("SELECT server_id, filename, kind FROM backups WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
-        .await?;
+        .await?
+        .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
 
-    if let Some((filename,)) = backup {
-         let backups_dir = std::path::Path::new("backups");
-         let file_path = backups_dir.join(filename);
-         if file_path.exists() {
-             fs::remove_file(file_path).await?;
-         }
-    }
+    auth.require_server(&server_id)?;
 
-    let result = sqlx::query("DELETE FROM backups WHERE id = ?")
+    backup::delete_backup_files(&filename, &kind)
+        .await
+        .map_err(|e| AppError::Internal(format!("Delete failed: {e}"))
+            .with_code(ErrorCode::BackupDeleteFailed))?;
+
+    sqlx::query("DELETE FROM backups WHERE id = ?")
         .bind(&id)
         .execute(&state.pool)
         .await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound));
-    }
-
     Ok(SuccessResponse::ok())
 }
 
 async fn restore_backup(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
+    body: Option<Json<RestoreBackupRequest>>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerBackupsRestore)?;
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+
     let backup: BackupRow = sqlx::query_as(
-        "SELECT id, server_id, filename, size_bytes, created_at FROM backups WHERE id = ?",
+        "SELECT * FROM backups WHERE id = ?",
     )
     .bind(&id)
     .fetch_optional(&state.pool)
     .await?
     .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
 
-    let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
+    auth.require_server(&backup.server_id)?;
+
+    let server: crate::api::servers::models::ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
         .bind(&backup.server_id)
         .fetch_optional(&state.pool)
         .await?
         .ok_or_else(|| AppError::NotFound("Server not found".into()).with_code(ErrorCode::ServerNotFound))?;
 
-    let backups_dir = std::path::Path::new("backups");
-    let file_path = backups_dir.join(&backup.filename);
-    
-    // If server is running, stop it first
+    // If server is running, stop it first; `stop` returns once the process has exited
     if state.process_manager.is_running(&backup.server_id) {
         state.process_manager.stop(&backup.server_id).await?;
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
     }
 
-    crate::services::system::backup::extract_archive(file_path.to_string_lossy().to_string(), server.0)
+    let snapshot_id = crate::services::system::restore::safe_restore(
+        &state.pool,
+        &server,
+        &backup.filename,
+        &backup.kind,
+        body.path,
+    ).await?;
+
+    let message = match snapshot_id {
+        Some(snapshot_id) => format!(
+            "Restored backup {} for server {} (previous state saved as backup {})",
+            backup.filename, backup.server_id, snapshot_id
+        ),
+        None => format!("Restored backup {} for server {}", backup.filename, backup.server_id),
+    };
+    Ok(SuccessResponse::with_message(message))
+}
+/// Stream a backup as a `.tar.gz`. Range requests are honoured so large downloads can be resumed.
+async fn download_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    request: Request,
+) -> Result<Response, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+
+    let backup: BackupRow = sqlx::query_as("SELECT * FROM backups WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
+
+    auth.require_server(&backup.server_id)?;
+
+    let path = backup::backups_dir().join(backup::safe_relative(&backup.filename)?);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Backup file not found: {}", backup.filename))
+            .with_code(ErrorCode::BackupNotFound));
+    }
+
+    // Incremental backups are exported to a plain archive first
+    let (serve_path, download_name, exported) = if backup.kind == backup::KIND_INCREMENTAL {
+        let name = format!("{}.tar.gz", backup.filename.trim_end_matches(chunk_store::MANIFEST_SUFFIX));
+        let export_path = backup::backups_dir().join(format!(".download-{}", Uuid::new_v4()));
+        if let Err(e) = chunk_store::export_archive(path, export_path.clone()).await {
+            let _ = tokio::fs::remove_file(&export_path).await;
+            return Err(e);
+        }
+        (export_path.clone(), name, Some(export_path))
+    } else {
+        (path, backup.filename.clone(), None)
+    };
+
+    let response = ServeFile::new(&serve_path)
+        .oneshot(request)
         .await
-        .map_err(|e| AppError::Internal(format!("Restore failed: {e}"))
-            .with_code(ErrorCode::BackupRestoreFailed))?;
+        .map_err(|e| AppError::Internal(format!("Failed to serve backup: {e}")))?;
 
-    Ok(SuccessResponse::with_message(format!("Restoring backup {} for server {}", backup.filename, backup.server_id)))
-}
\ No newline at end of file
+    // The export is already open at this point, so it can be unlinked while it streams
+    if let Some(export_path) = exported {
+        let _ = tokio::fs::remove_file(export_path).await;
+    }
+
+    let mut response = response.map(Body::new);
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{download_name}\""))
+        .map_err(|e| AppError::Internal(format!("Invalid backup file name: {e}")))?;
+    response.headers_mut().insert(header::CONTENT_DISPOSITION, disposition);
+    Ok(response)
+}
+
+/// Register a `.tar.gz` made elsewhere (another host, a manual copy) as an archive backup of a server.
+/// The archive is sent as the `file` field of a multipart form.
+async fn import_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<ImportBackupQuery>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+    auth.require(Permission::ServerBackupsCreate)?;
+    auth.require_server(&query.server_id)?;
+
+    let server_exists: Option<String> = sqlx::query_scalar("SELECT id FROM servers WHERE id = ?")
+        .bind(&query.server_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    if server_exists.is_none() {
+        return Err(AppError::NotFound("Server not found".into()).with_code(ErrorCode::ServerNotFound));
+    }
+
+    let upload_path = backup::backups_dir().join(format!(".upload-{}", Uuid::new_v4()));
+    let filename = backup::backup_filename("import", &query.server_id, backup::KIND_ARCHIVE);
+    let stored = async {
+        receive_upload(&mut multipart, &upload_path).await?;
+        let checksum = backup::validate_import(upload_path.clone()).await?;
+        tokio::fs::rename(&upload_path, backup::backups_dir().join(&filename)).await?;
+        Ok::<_, AppError>(checksum)
+    };
+    let checksum = match stored.await {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&upload_path).await;
+            return Err(e);
+        }
+    };
+
+    let size_bytes = tokio::fs::metadata(backup::backups_dir().join(&filename)).await?.len() as i64;
+    let id = Uuid::new_v4().to_string();
+    let created_at = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO backups (id, server_id, filename, size_bytes, kind, checksum, origin, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&query.server_id)
+    .bind(&filename)
+    .bind(size_bytes)
+    .bind(backup::KIND_ARCHIVE)
+    .bind(&checksum)
+    .bind(backup::ORIGIN_IMPORT)
+    .bind(&created_at)
+    .execute(&state.pool)
+    .await?;
+
+    replication::spawn_replication(state.pool.clone(), query.server_id.clone(), filename.clone(), backup::KIND_ARCHIVE.to_string());
+
+    created_response(&state, &id).await
+}
+
+/// Write the `file` field of the form to `path` as it arrives
+async fn receive_upload(multipart: &mut Multipart, path: &std::path::Path) -> Result<(), AppError> {
+    while let Some(mut field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let mut file = tokio::fs::File::create(path).await?;
+        while let Some(chunk) = field.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        return Ok(());
+    }
+
+    Err(AppError::BadRequest("backups.missing_file".into()).with_code(ErrorCode::MissingRequiredField))
+}
+
+/// Response for a backup row just inserted
+async fn created_response(state: &AppState, id: &str) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+    let backup: BackupRow = sqlx::query_as("SELECT * FROM backups WHERE id = ?")
+        .bind(id)
+        .fetch_one(&state.pool)
+        .await?;
+    Ok((StatusCode::CREATED, Json(backup.into())))
+}
+
+async fn find_backup(state: &AppState, auth: &AuthUser, id: &str) -> Result<BackupRow, AppError> {
+    let backup: BackupRow = sqlx::query_as("SELECT * FROM backups WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
+
+    auth.require_server(&backup.server_id)?;
+    Ok(backup)
+}
+
+async fn list_entries(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<EntriesQuery>,
+) -> Result<Json<Vec<browse::BackupEntry>>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+    let backup = find_backup(&state, &auth, &id).await?;
+
+    let mut entries = browse::list_backup(&backup.filename, &backup.kind).await?;
+    if let Some(root) = query.path.as_deref().map(|p| p.trim_matches('/')).filter(|p| !p.is_empty()) {
+        entries.retain(|e| backup::is_within(&e.path, root));
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Json(entries))
+}
+
+/// Changes from this backup to another one, or to the live working directory
+async fn diff_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<browse::BackupDiff>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+    let backup = find_backup(&state, &auth, &id).await?;
+    let old = browse::list_backup(&backup.filename, &backup.kind).await?;
+
+    let new = if query.against == "live" {
+        let server: crate::api::servers::models::ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+            .bind(&backup.server_id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Server not found".into()).with_code(ErrorCode::ServerNotFound))?;
+        browse::list_live(server.working_dir.clone(), server.backup_filter()?).await?
+    } else {
+        let other = find_backup(&state, &auth, &query.against).await?;
+        browse::list_backup(&other.filename, &other.kind).await?
+    };
+
+    Ok(Json(browse::diff(old, new)))
+}
+
+/// Stream a single file out of a backup
+async fn read_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<FileQuery>,
+) -> Result<Response, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+    let backup = find_backup(&state, &auth, &id).await?;
+
+    let (size, content) = browse::read_file(&backup.filename, &backup.kind, &query.path).await?;
+
+    let file_name = query.path.rsplit('/').next().unwrap_or("download").replace('"', "");
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{file_name}\""))
+        .unwrap_or_else(|_| HeaderValue::from_static("attachment"));
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .header(header::CONTENT_LENGTH, size.to_string())
+        .body(Body::from_stream(content))
+        .map_err(|e| AppError::Internal(format!("Failed to build response: {e}")))
+}
+
+/// Re-read the backup end to end and compare it with its checksum.
+/// A corrupted backup is not an error: the result is returned and stored on the backup.
+async fn verify_backup(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<BackupResponse>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+
+    let backup: BackupRow = sqlx::query_as("SELECT * FROM backups WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Backup not found".into()).with_code(ErrorCode::BackupNotFound))?;
+
+    auth.require_server(&backup.server_id)?;
+
+    integrity::verify(&state.pool, &backup.id, &backup.filename, &backup.kind, backup.checksum.as_deref()).await?;
+
+    let backup: BackupRow = sqlx::query_as("SELECT * FROM backups WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    Ok(Json(backup.into()))
+}
+
+async fn list_replicas(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<ListBackupsQuery>,
+) -> Result<Json<Vec<ReplicaRow>>, AppError> {
+    auth.require(Permission::ServerBackupsView)?;
+
+    let replicas: Vec<ReplicaRow> = if let Some(server_id) = &query.server_id {
+        auth.require_server(server_id)?;
+        sqlx::query_as("SELECT * FROM backup_replicas WHERE server_id = ? ORDER BY created_at DESC")
+            .bind(server_id)
+            .fetch_all(&state.pool)
+            .await?
+    } else {
+        sqlx::query_as("SELECT * FROM backup_replicas ORDER BY created_at DESC")
+            .fetch_all(&state.pool)
+            .await?
+    };
+
+    Ok(Json(replicas.into_iter().filter(|r| auth.can_access_server(&r.server_id)).collect()))
+}
+
+async fn find_replica(state: &AppState, id: &str) -> Result<ReplicaRow, AppError> {
+    sqlx::query_as("SELECT * FROM backup_replicas WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Backup replica not found".into()).with_code(ErrorCode::BackupNotFound))
+}
+
+/// Download a replica back as a local backup, so it can be restored
+async fn fetch_replica(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<BackupResponse>), AppError> {
+    auth.require(Permission::ServerBackupsCreate)?;
+
+    let replica = find_replica(&state, &id).await?;
+    auth.require_server(&replica.server_id)?;
+
+    let (size_bytes, checksum) = replication::fetch_replica(&state.pool, &replica).await?;
+    let backup_id = Uuid::new_v4().to_string();
+    let created_at = Utc::now().to_rfc3339();
+
+    // Replicas are always plain archives
+    sqlx::query(
+        "INSERT INTO backups (id, server_id, filename, size_bytes, kind, checksum, origin, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&backup_id)
+    .bind(&replica.server_id)
+    .bind(&replica.filename)
+    .bind(size_bytes as i64)
+    .bind(backup::KIND_ARCHIVE)
+    .bind(&checksum)
+    .bind(backup::ORIGIN_REPLICA)
+    .bind(&created_at)
+    .execute(&state.pool)
+    .await?;
+
+    created_response(&state, &backup_id).await
+}
+
+async fn delete_replica(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerBackupsDelete)?;
+
+    let replica = find_replica(&state, &id).await?;
+    auth.require_server(&replica.server_id)?;
+
+    let target = replication::load_destination(&state.pool, &replica.destination_id).await?.target();
+    replication::delete_replica(&state.pool, target.as_ref(), &replica.id, &replica.server_id, &replica.filename).await?;
+
+    Ok(SuccessResponse::ok())
+}
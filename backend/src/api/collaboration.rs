@@ -40,6 +40,7 @@ pub struct CreateMessageRequest {
 
 async fn list_messages(
     State(state): State<AppState>,
+    _auth: AuthUser,
 ) -> Result<Json<Vec<MessageRow>>, AppError> {
     let messages: Vec<MessageRow> = sqlx::query_as(
         r#"
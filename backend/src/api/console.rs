@@ -4,16 +4,101 @@ use axum::{
     http::HeaderMap,
 };
 use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 use futures::{sink::SinkExt, stream::StreamExt};
 
 use crate::core::AppState;
-use crate::api::auth::Claims;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::game::events::{parse_level, ConsoleEvent};
+
+/// Version of the JSON protocol, sent in every message as `v`
+const PROTOCOL_VERSION: u32 = 1;
 
 #[derive(Debug, Deserialize)]
 pub struct WsQuery {
     pub token: Option<String>,
+    /// "text" (default) or "json"
+    pub protocol: Option<String>,
+}
+
+/// How console messages are framed on the socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    /// One console line per message, commands sent as raw text
+    Text,
+    /// Typed JSON messages, commands acknowledged
+    Json,
+}
+
+impl Protocol {
+    fn parse(value: Option<&str>) -> Option<Self> {
+        match value.unwrap_or("text") {
+            "text" => Some(Protocol::Text),
+            "json" => Some(Protocol::Json),
+            _ => None,
+        }
+    }
+
+    fn render(self, event: &ConsoleEvent) -> Option<String> {
+        match self {
+            Protocol::Text => event.to_text(),
+            Protocol::Json => Some(event_message(event).to_string()),
+        }
+    }
+}
+
+fn event_message(event: &ConsoleEvent) -> Value {
+    match event {
+        ConsoleEvent::Log { line, stream, timestamp } => json!({
+            "v": PROTOCOL_VERSION,
+            "type": "log",
+            "line": line,
+            "stream": stream,
+            "timestamp": timestamp.to_rfc3339(),
+            "level": parse_level(line),
+        }),
+        ConsoleEvent::Status(status) => json!({ "v": PROTOCOL_VERSION, "type": "status", "status": status }),
+        ConsoleEvent::Metrics(metrics) => json!({ "v": PROTOCOL_VERSION, "type": "metrics", "metrics": metrics }),
+        ConsoleEvent::Player { action, name } => json!({
+            "v": PROTOCOL_VERSION,
+            "type": "player",
+            "action": action,
+            "name": name,
+        }),
+        ConsoleEvent::Notice { source, message } => json!({
+            "v": PROTOCOL_VERSION,
+            "type": "notice",
+            "source": source,
+            "message": message,
+        }),
+    }
+}
+
+/// Message of a JSON client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Command {
+        command: String,
+        /// Echoed back in the acknowledgement
+        id: Option<Value>,
+    },
+}
+
+fn command_ack(id: Option<Value>, result: Result<(), String>) -> String {
+    json!({
+        "v": PROTOCOL_VERSION,
+        "type": "command_ack",
+        "id": id,
+        "ok": result.is_ok(),
+        "error": result.err(),
+    })
+    .to_string()
 }
 
 pub async fn ws_handler(
@@ -38,34 +123,48 @@ pub async fn ws_handler(
     })?;
     
     // Manual token verification
-    let secret = crate::core::database::get_or_create_jwt_secret(&state.pool).await
-        .map_err(|_| AppError::Internal("Failed to get secret".into()))?;
-    
-    let _token_data = jsonwebtoken::decode::<Claims>(
-        &token,
-        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-        &jsonwebtoken::Validation::default(),
-    ).map_err(|e| {
-        warn!("WebSocket connection rejected: Invalid token: {}", e);
-        AppError::Unauthorized("Invalid token".into())
+    let auth = AuthUser::from_token(&state, &token).await.map_err(|e| {
+        warn!("WebSocket connection rejected: {}", e);
+        e
+    })?;
+    auth.require(Permission::ServerConsoleRead)?;
+    auth.require_server(&server_id)?;
+
+    let protocol = Protocol::parse(query.protocol.as_deref()).ok_or_else(|| {
+        AppError::BadRequest("console.unsupported_protocol".into()).with_code(ErrorCode::ValidationFailed)
     })?;
+    let can_write = auth.has_permission(Permission::ServerConsoleWrite);
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, server_id, state)))
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, server_id, state, can_write, protocol)))
 }
 
-async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
+async fn handle_socket(socket: WebSocket, server_id: String, state: AppState, can_write: bool, protocol: Protocol) {
     let pm = state.process_manager;
-    let mut log_rx = pm.subscribe_logs(&server_id);
+    let (backlog, mut log_rx) = pm.subscribe_console(&server_id).await;
 
     info!("WebSocket connected for server: {}", server_id);
 
     let (mut sender, mut receiver) = socket.split();
 
-    // Send last known metrics immediately
-    if let Some(metrics) = pm.get_last_metrics(&server_id).await {
-        let _ = sender.send(Message::Text(metrics)).await;
+    if protocol == Protocol::Json {
+        let hello = json!({ "v": PROTOCOL_VERSION, "type": "hello", "protocol": PROTOCOL_VERSION });
+        if sender.send(Message::Text(hello.to_string())).await.is_err() {
+            return;
+        }
     }
 
+    // Replay the recent console, then send the last known metrics
+    let metrics = pm.get_last_metrics(&server_id).await.map(ConsoleEvent::Metrics);
+    for event in backlog.iter().chain(metrics.as_ref()) {
+        let Some(message) = protocol.render(event) else { continue };
+        if sender.send(Message::Text(message)).await.is_err() {
+            return;
+        }
+    }
+
+    // Acknowledgements of JSON commands, sent along with the logs
+    let (ack_tx, mut ack_rx) = mpsc::unbounded_channel::<String>();
+
     // Task to handle incoming messages (commands from client)
     let mut recv_task = {
         let pm = pm.clone();
@@ -74,7 +173,27 @@ async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
         tokio::spawn(async move {
             while let Some(Ok(msg)) = receiver.next().await {
                 match msg {
+                    Message::Text(text) if protocol == Protocol::Json => {
+                        let (id, result) = match serde_json::from_str::<ClientMessage>(&text) {
+                            Ok(ClientMessage::Command { id, .. }) if !can_write => {
+                                (id, Err("console.write_forbidden".to_string()))
+                            }
+                            Ok(ClientMessage::Command { command, id }) => {
+                                let result = pm.send_command(&server_id, &command).await.map_err(|e| {
+                                    error!("Failed to send command: {}", e);
+                                    e.to_string()
+                                });
+                                (id, result)
+                            }
+                            Err(e) => (None, Err(format!("Invalid message: {e}"))),
+                        };
+                        let _ = ack_tx.send(command_ack(id, result));
+                    }
                     Message::Text(text) => {
+                         if !can_write {
+                             warn!("Ignoring console command without permission for server {}", server_id);
+                             continue;
+                         }
                          if let Err(e) = pm.send_command(&server_id, &text).await {
                              error!("Failed to send command: {}", e);
                          }
@@ -90,16 +209,21 @@ async fn handle_socket(socket: WebSocket, server_id: String, state: AppState) {
     let server_id_clone = server_id.clone();
     let mut send_task = tokio::spawn(async move {
         loop {
-            match log_rx.recv().await {
-                Ok(log_line) => {
-                    if sender.send(Message::Text(log_line)).await.is_err() {
+            let message = tokio::select! {
+                event = log_rx.recv() => match event {
+                    Ok(event) => protocol.render(&event),
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        error!("WebSocket lagged, skipped {} messages for server {}", n, server_id_clone);
+                        None
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                         return;
                     }
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    error!("WebSocket lagged, skipped {} messages for server {}", n, server_id_clone);
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                },
+                Some(ack) = ack_rx.recv() => Some(ack),
+            };
+            if let Some(message) = message {
+                if sender.send(Message::Text(message)).await.is_err() {
                     return;
                 }
             }
@@ -1,6 +1,6 @@
 use axum::{
     routing::get,
-    extract::Query,
+    extract::{Query, State},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
@@ -8,6 +8,9 @@ use std::path::PathBuf;
 use tokio::fs;
 
 use crate::core::{AppState, AppError};
+use crate::core::error::codes::ErrorCode;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
@@ -26,7 +29,21 @@ pub struct ListQuery {
     pub path: Option<String>,
 }
 
-async fn list_directory(Query(query): Query<ListQuery>) -> Result<Json<serde_json::Value>, AppError> {
+async fn list_directory(
+    State(state): State<AppState>,
+    auth: Option<AuthUser>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    // The directory picker is used by the first-time setup, before any account exists
+    let users_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&state.pool)
+        .await?;
+    if users_count > 0 {
+        let auth = auth.ok_or_else(|| AppError::Unauthorized("auth.missing_auth_header".into())
+            .with_code(ErrorCode::AuthMissingHeader))?;
+        auth.require(Permission::SettingsManage)?;
+    }
+
     // Déterminer le répertoire racine autorisé
     let allowed_base = std::env::var("DATA_DIR")
         .unwrap_or_else(|_| "/".to_string());
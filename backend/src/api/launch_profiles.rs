@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use axum::{
+    routing::get,
+    extract::{State, Path},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+
+use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
+use crate::api::SuccessResponse;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::game::launch::{self, LaunchProfile};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_profiles))
+        .route("/:game_type", get(get_profile).put(save_profile).delete(delete_profile))
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchProfileResponse {
+    pub game_type: String,
+    pub command: String,
+    pub env: HashMap<String, String>,
+    pub stop_command: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchProfilesResponse {
+    pub profiles: Vec<LaunchProfileResponse>,
+    /// Variables usable in commands and environment values, e.g. `{port}`
+    pub variables: Vec<&'static str>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchProfileRequest {
+    pub command: String,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub stop_command: Option<String>,
+}
+
+impl From<LaunchProfile> for LaunchProfileResponse {
+    fn from(profile: LaunchProfile) -> Self {
+        Self {
+            env: profile.env(),
+            game_type: profile.game_type,
+            command: profile.command,
+            stop_command: profile.stop_command,
+            created_at: profile.created_at,
+            updated_at: profile.updated_at,
+        }
+    }
+}
+
+fn not_found() -> AppError {
+    AppError::NotFound("launch_profiles.not_found".into()).with_code(ErrorCode::ServerLaunchProfileNotFound)
+}
+
+async fn list_profiles(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<LaunchProfilesResponse>, AppError> {
+    // Profiles are also listed when editing a server's launch command
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    let profiles: Vec<LaunchProfile> = sqlx::query_as("SELECT * FROM launch_profiles ORDER BY game_type")
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(LaunchProfilesResponse {
+        profiles: profiles.into_iter().map(Into::into).collect(),
+        variables: launch::VARIABLES.to_vec(),
+    }))
+}
+
+async fn get_profile(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(game_type): Path<String>,
+) -> Result<Json<LaunchProfileResponse>, AppError> {
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    let profile = launch::load_profile(&state.pool, &game_type).await?.ok_or_else(not_found)?;
+    Ok(Json(profile.into()))
+}
+
+/// Create the profile of a game type, or replace it
+async fn save_profile(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(game_type): Path<String>,
+    Json(body): Json<LaunchProfileRequest>,
+) -> Result<Json<LaunchProfileResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    let game_type = game_type.trim().to_lowercase();
+    if game_type.is_empty() {
+        return Err(AppError::BadRequest("launch_profiles.invalid_game_type".into()).with_code(ErrorCode::ValidationFailed));
+    }
+    launch::validate_command(&body.command)?;
+    launch::validate_env(&body.env)?;
+
+    let env = serde_json::to_string(&body.env)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize environment: {e}")))?;
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "INSERT INTO launch_profiles (game_type, command, env, stop_command, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(game_type) DO UPDATE SET
+         command = excluded.command, env = excluded.env, stop_command = excluded.stop_command, updated_at = excluded.updated_at"
+    )
+    .bind(&game_type)
+    .bind(body.command.trim())
+    .bind(env)
+    .bind(body.stop_command.as_deref().map(str::trim).filter(|c| !c.is_empty()))
+    .bind(&now)
+    .bind(&now)
+    .execute(&state.pool)
+    .await?;
+
+    let profile = launch::load_profile(&state.pool, &game_type).await?.ok_or_else(not_found)?;
+    Ok(Json(profile.into()))
+}
+
+/// Servers of the game type fall back to the Hytale command; built-in profiles come back with their defaults at the next start
+async fn delete_profile(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(game_type): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    let result = sqlx::query("DELETE FROM launch_profiles WHERE game_type = ?")
+        .bind(game_type.to_lowercase())
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(not_found());
+    }
+
+    Ok(SuccessResponse::ok())
+}
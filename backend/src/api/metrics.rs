@@ -9,6 +9,8 @@ use tracing::debug;
 use uuid::Uuid;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::core::error::AppError;
 
 /// A single metric data point
@@ -47,9 +49,13 @@ pub fn routes() -> Router<AppState> {
 /// Returns historical metrics for a server
 async fn get_server_metrics(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Query(query): Query<MetricsQuery>,
 ) -> Result<Json<MetricsHistoryResponse>, AppError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&server_id)?;
+
     let period = query.period.unwrap_or_else(|| "1d".to_string());
     
     // Calculate time threshold based on period
@@ -24,12 +24,15 @@ impl SuccessResponse {
 }
 
 pub mod auth;
+pub mod backup_destinations;
 pub mod backups;
 pub mod collaboration;
 pub mod console;
 pub mod filesystem;
+pub mod launch_profiles;
 pub mod metrics;
 pub mod roles;
+pub mod runtimes;
 pub mod servers;
 pub mod settings;
 pub mod setup;
@@ -42,12 +45,15 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .nest("/auth", auth::routes())
         .nest("/backups", backups::routes())
+        .nest("/backup-destinations", backup_destinations::routes())
         .nest("/collaboration", collaboration::routes())
         .nest("/filesystem", filesystem::routes())
+        .nest("/launch-profiles", launch_profiles::routes())
         .nest("/servers", servers::routes()) // servers::routes() now includes metrics merging inside it if kept consistent
         .nest("/settings", settings::routes())
         .nest("/setup", setup::routes())
         .nest("/roles", roles::routes())
+        .nest("/runtimes", runtimes::routes())
         .nest("/system", system::routes())
         .nest("/upload", upload::routes())
         .nest("/users", users::routes())
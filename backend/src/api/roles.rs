@@ -9,6 +9,8 @@ use uuid::Uuid;
 use chrono::Utc;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
 
@@ -52,7 +54,13 @@ pub struct UpdateRoleRequest {
 
 async fn list_roles(
     State(state): State<AppState>,
+    auth: AuthUser,
 ) -> Result<Json<Vec<RoleResponse>>, AppError> {
+    // Roles are also listed when assigning them to users
+    if !auth.has_permission(Permission::UsersManage) {
+        auth.require(Permission::RolesManage)?;
+    }
+
     let roles: Vec<RoleRow> = sqlx::query_as("SELECT * FROM roles ORDER BY created_at ASC")
         .fetch_all(&state.pool)
         .await?;
@@ -74,8 +82,14 @@ async fn list_roles(
 
 async fn get_role(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<RoleResponse>, AppError> {
+    // Roles are also listed when assigning them to users
+    if !auth.has_permission(Permission::UsersManage) {
+        auth.require(Permission::RolesManage)?;
+    }
+
     let role: RoleRow = sqlx::query_as("SELECT * FROM roles WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -96,8 +110,11 @@ async fn get_role(
 
 async fn create_role(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateRoleRequest>,
 ) -> Result<Json<RoleResponse>, AppError> {
+    auth.require(Permission::RolesManage)?;
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let permissions_json = serde_json::to_string(&body.permissions).unwrap_or_else(|_| "[]".to_string());
@@ -125,9 +142,12 @@ async fn create_role(
 
 async fn update_role(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(body): Json<UpdateRoleRequest>,
 ) -> Result<Json<RoleResponse>, AppError> {
+    auth.require(Permission::RolesManage)?;
+
     let role: RoleRow = sqlx::query_as("SELECT * FROM roles WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -169,8 +189,11 @@ async fn update_role(
 
 async fn delete_role(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::RolesManage)?;
+
     let role: RoleRow = sqlx::query_as("SELECT * FROM roles WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -0,0 +1,90 @@
+use axum::{
+    routing::get,
+    extract::{State, Path},
+    Json, Router,
+    http::StatusCode,
+};
+use serde::Deserialize;
+
+use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
+use crate::api::SuccessResponse;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::game::runtime::{self, JavaRuntime};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_runtimes).post(install_runtime))
+        .route("/:id", get(get_runtime).delete(delete_runtime))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InstallRuntimeRequest {
+    /// "Java <version>" by default
+    pub name: Option<String>,
+    /// Mirror to download the .tar.gz archive from
+    pub url: Option<String>,
+    /// Archive already on the panel host
+    pub archive_path: Option<String>,
+    /// Expected SHA-256 of the archive, in hex
+    pub sha256: String,
+}
+
+async fn list_runtimes(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<JavaRuntime>>, AppError> {
+    // Runtimes are also listed when editing a server
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    Ok(Json(runtime::list(&state.pool).await?))
+}
+
+async fn get_runtime(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<JavaRuntime>, AppError> {
+    if !auth.has_permission(Permission::ServerEdit) {
+        auth.require(Permission::SettingsManage)?;
+    }
+
+    Ok(Json(runtime::load(&state.pool, &id).await?.ok_or_else(runtime::not_found)?))
+}
+
+/// Download or unpack a JDK; the request returns once it is installed
+async fn install_runtime(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<InstallRuntimeRequest>,
+) -> Result<(StatusCode, Json<JavaRuntime>), AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    let source = match (body.url.as_deref(), body.archive_path.as_deref()) {
+        (Some(url), None) if url.starts_with("http://") || url.starts_with("https://") => url,
+        (None, Some(path)) if !path.trim().is_empty() => path.trim(),
+        _ => return Err(AppError::BadRequest("runtimes.invalid_source".into()).with_code(ErrorCode::ValidationFailed)),
+    };
+    let sha256 = body.sha256.trim();
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::BadRequest("runtimes.invalid_checksum".into()).with_code(ErrorCode::ValidationFailed));
+    }
+
+    let installed = runtime::install(&state.pool, body.name.as_deref(), source, sha256).await?;
+    Ok((StatusCode::CREATED, Json(installed)))
+}
+
+async fn delete_runtime(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
+    runtime::remove(&state.pool, &id).await?;
+    Ok(SuccessResponse::ok())
+}
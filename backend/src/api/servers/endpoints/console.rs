@@ -1,18 +1,85 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::game::logs::{self, HistoryPage, HistoryQuery};
 use crate::api::servers::models::CommandRequest;
 
 pub async fn send_command(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(body): Json<CommandRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerConsoleWrite)?;
+    auth.require_server(&id)?;
+
     state.process_manager.send_command(&id, &body.command).await?;
     Ok(SuccessResponse::ok())
 }
+
+const HISTORY_PAGE_LINES: usize = 200;
+const HISTORY_MAX_LINES: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryParams {
+    /// Regular expression the lines must match
+    pub query: Option<String>,
+    /// RFC 3339 date; older runs are left out
+    pub since: Option<String>,
+    /// Cursor of the previous page: dated log name of the run and byte offset
+    pub run: Option<String>,
+    pub before: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// Console lines of the current and past runs, newest page first
+pub async fn console_history(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<HistoryPage>, AppError> {
+    auth.require(Permission::ServerConsoleRead)?;
+    auth.require_server(&id)?;
+
+    let invalid = || AppError::BadRequest("console.invalid_history_query".into()).with_code(ErrorCode::ValidationFailed);
+    let pattern = match params.query.as_deref().filter(|q| !q.is_empty()) {
+        Some(query) => Some(Regex::new(query).map_err(|_| invalid())?),
+        None => None,
+    };
+    let since = match params.since.as_deref() {
+        Some(since) => Some(DateTime::parse_from_rfc3339(since).map_err(|_| invalid())?.with_timezone(&Utc)),
+        None => None,
+    };
+
+    let (working_dir,): (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("servers.not_found".into()).with_code(ErrorCode::ServerNotFound))?;
+
+    let page = tokio::task::spawn_blocking(move || {
+        logs::search_history(std::path::Path::new(&working_dir), &HistoryQuery {
+            pattern: pattern.as_ref(),
+            since,
+            run: params.run.as_deref(),
+            before: params.before,
+            limit: params.limit.unwrap_or(HISTORY_PAGE_LINES).clamp(1, HISTORY_MAX_LINES),
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?;
+
+    Ok(Json(page))
+}
@@ -11,18 +11,30 @@ use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::services::system::backup;
+use crate::services::system::replication::{self, ReplicationRule};
 use crate::utils::memory::{parse_memory_to_bytes, calculate_total_memory};
 use crate::utils::templates;
 use crate::core::database::DbPool;
+use crate::api::system;
+use crate::services::game::{container, jvm, launch, runtime};
+use crate::services::game::ProcessManager;
+use crate::services::game::manager::DEFAULT_STOP_TIMEOUT_SECS;
 
 use crate::api::servers::models::{ServerRow, ServerResponse, CreateServerRequest, Player, PlayerRow};
 use super::lifecycle::spawn_hytale_installation;
 
 pub async fn list_servers(
     State(state): State<AppState>,
+    auth: AuthUser,
 ) -> Result<Json<Vec<ServerResponse>>, AppError> {
+    auth.require(Permission::ServerView)?;
+
     let servers: Vec<ServerRow> = sqlx::query_as(
         "SELECT * FROM servers"
     )
@@ -32,19 +44,9 @@ pub async fn list_servers(
     let mut responses = Vec::new();
     let pm = &state.process_manager;
     
-    for s in servers {
+    for s in servers.into_iter().filter(|s| auth.can_access_server(&s.id)) {
         let dir_exists = StdPath::new(&s.working_dir).exists();
-        let is_running = pm.is_running(&s.id);
-        
-        let status = if !dir_exists { 
-            "missing" 
-        } else if pm.is_installing(&s.id) {
-            if pm.is_auth_required(&s.id) { "auth_required" } else { "installing" }
-        } else if is_running {
-             if pm.is_auth_required(&s.id) { "auth_required" } else { "running" }
-        } else {
-            "stopped"
-        };
+        let (status, is_running) = server_status(pm, &s.id, dir_exists).await;
 
         let mut players_vec = Vec::new();
         if is_running {
@@ -98,6 +100,10 @@ pub async fn list_servers(
         
         let notifications = s.discord_notifications.as_ref()
             .and_then(|n| serde_json::from_str(n).ok());
+        let depends_on = s.dependencies();
+        let (backup_include, backup_exclude) = backup::rules_from_columns(s.backup_include.as_deref(), s.backup_exclude.as_deref());
+        let backup_replication = s.replication_rules();
+        let stop_countdown = s.stop_countdown();
 
         responses.push(ServerResponse {
             id: s.id,
@@ -110,8 +116,11 @@ pub async fn list_servers(
             min_memory: s.min_memory,
             max_memory: s.max_memory,
             extra_args: s.extra_args,
+            jvm_profile: s.jvm_profile,
+            java_runtime: s.java_runtime,
             config: config_json.clone(),
             auto_start: s.auto_start != 0,
+            depends_on,
             created_at: s.created_at,
             updated_at: s.updated_at,
             dir_exists,
@@ -125,12 +134,31 @@ pub async fn list_servers(
             backup_frequency: s.backup_frequency as u32,
             backup_max_backups: s.backup_max_backups as u32,
             backup_prefix: s.backup_prefix,
+            backup_mode: s.backup_mode,
+            backup_include,
+            backup_exclude,
+            backup_replication,
             discord_username: s.discord_username,
             discord_avatar: s.discord_avatar,
             discord_webhook_url: s.discord_webhook_url,
             discord_notifications: notifications,
             logs_retention_days: s.logs_retention_days as u32,
             watchdog_enabled: s.watchdog_enabled != 0,
+            stop_command: s.stop_command,
+            stop_timeout: s.stop_timeout as u32,
+            stop_countdown,
+            execution_backend: s.execution_backend,
+            container_image: s.container_image,
+            cpu_limit: s.cpu_limit,
+            memory_limit: s.memory_limit,
+            pids_limit: s.pids_limit.and_then(|p| u32::try_from(p).ok()),
+            io_weight: s.io_weight.and_then(|w| u16::try_from(w).ok()),
+            launch_env: launch::env_from_column(s.launch_env.as_deref()),
+            launch_command: s.launch_command,
+            aot_cache: s.aot_cache != 0,
+            disable_sentry: s.disable_sentry != 0,
+            game_backup_enabled: s.game_backup_enabled != 0,
+            game_backup_frequency: s.game_backup_frequency.and_then(|m| u32::try_from(m).ok()),
             auth_mode: s.auth_mode,
 
             cpu_usage: cpu,
@@ -148,8 +176,30 @@ pub async fn list_servers(
 
 pub async fn create_server(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateServerRequest>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require(Permission::ServerCreate)?;
+    validate_backup_mode(body.backup_mode.as_deref())?;
+    backup::BackupFilter::new(
+        body.backup_include.as_deref().unwrap_or_default(),
+        body.backup_exclude.as_deref().unwrap_or_default(),
+    )?;
+    if let Some(rules) = &body.backup_replication {
+        validate_replication_rules(&state.pool, rules).await?;
+    }
+    validate_stop_settings(&body)?;
+    validate_execution(&body)?;
+    validate_java(&state.pool, &body).await?;
+
+    // The typed option first, then the one of the raw config; either must be a known mode
+    let auth_mode = body.auth_mode.as_deref()
+        .or_else(|| body.config.as_ref().and_then(|c| c.get("auth_mode")).and_then(|v| v.as_str()))
+        .unwrap_or("authenticated");
+    if !launch::AUTH_MODES.contains(&auth_mode) {
+        return Err(AppError::BadRequest("servers.invalid_launch_options".into()).with_code(ErrorCode::ValidationFailed));
+    }
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let auto_start = body.auto_start.unwrap_or(false) as i32;
@@ -182,10 +232,6 @@ pub async fn create_server(
     let config_value = body.config.as_ref();
     let server_name = &body.name;
 
-    let auth_mode = config_value
-        .and_then(|c| c.get("auth_mode"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("authenticated");
     let bind_address = config_value
         .and_then(|c| c.get("bind_address"))
         .and_then(|v| v.as_str())
@@ -204,6 +250,12 @@ pub async fn create_server(
     }
 
     let config_str = body.config.as_ref().map(|c| c.to_string());
+    let depends_on_str = body.depends_on.as_ref().and_then(|d| serde_json::to_string(d).ok());
+    let launch_env_str = body.launch_env.as_ref().and_then(|e| serde_json::to_string(e).ok());
+    let backup_include_str = body.backup_include.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let backup_exclude_str = body.backup_exclude.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let backup_replication_str = body.backup_replication.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let stop_countdown_str = body.stop_countdown.as_ref().and_then(|c| serde_json::to_string(c).ok());
     let actual_working_dir = server_base_path.to_str().unwrap_or(&body.working_dir);
     let actual_executable_str = &final_executable;
 
@@ -222,13 +274,21 @@ pub async fn create_server(
             backup_enabled, backup_frequency, backup_max_backups, backup_prefix,
             discord_username, discord_avatar, discord_webhook_url, discord_notifications,
             logs_retention_days, watchdog_enabled,
-            auth_mode, bind_address, port, nice_level
+            auth_mode, bind_address, port, nice_level, depends_on, backup_mode,
+            execution_backend, container_image, cpu_limit, memory_limit, pids_limit, io_weight,
+            launch_command, launch_env, aot_cache, disable_sentry, game_backup_enabled, game_backup_frequency,
+            jvm_profile, java_runtime, backup_include, backup_exclude, backup_replication,
+            stop_command, stop_timeout, stop_countdown
         ) VALUES (
             ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
             1, 30, 7, 'hytale_backup',
             'Hytale Bot', '', '', '{}',
             7, 1,
-            ?, ?, ?, ?
+            ?, ?, ?, ?, ?, ?,
+            ?, ?, ?, ?, ?, ?,
+            ?, ?, ?, ?, ?, ?,
+            ?, ?, ?, ?, ?,
+            ?, ?, ?
         )",
     )
     .bind(&id)
@@ -248,9 +308,42 @@ pub async fn create_server(
     .bind(bind_address)
     .bind(port)
     .bind(body.nice_level.unwrap_or(0))
+    .bind(depends_on_str)
+    .bind(body.backup_mode.as_deref().unwrap_or(backup::KIND_ARCHIVE))
+    .bind(body.execution_backend.as_deref().unwrap_or(container::BACKEND_NATIVE))
+    .bind(&body.container_image)
+    .bind(body.cpu_limit)
+    .bind(&body.memory_limit)
+    .bind(body.pids_limit)
+    .bind(body.io_weight)
+    .bind(&body.launch_command)
+    .bind(launch_env_str)
+    .bind(body.aot_cache.unwrap_or(true) as i32)
+    .bind(body.disable_sentry.unwrap_or(false) as i32)
+    .bind(body.game_backup_enabled.unwrap_or(false) as i32)
+    .bind(body.game_backup_frequency)
+    .bind(&body.jvm_profile)
+    .bind(&body.java_runtime)
+    .bind(backup_include_str)
+    .bind(backup_exclude_str)
+    .bind(backup_replication_str)
+    .bind(&body.stop_command)
+    .bind(body.stop_timeout.map_or(DEFAULT_STOP_TIMEOUT_SECS, i64::from))
+    .bind(stop_countdown_str)
     .execute(&state.pool)
     .await?;
 
+    // Non-admin creators are scoped to their allocated servers, so grant them the new one
+    if !auth.is_admin() {
+        let mut allocated = auth.allocated_servers.clone();
+        allocated.push(id.clone());
+        sqlx::query("UPDATE users SET allocated_servers = ? WHERE id = ?")
+            .bind(serde_json::to_string(&allocated).unwrap_or_else(|_| "[]".into()))
+            .bind(&auth.id)
+            .execute(&state.pool)
+            .await?;
+    }
+
     Ok((StatusCode::CREATED, Json(serde_json::json!({ 
         "id": id,
         "working_dir": actual_working_dir,
@@ -260,8 +353,11 @@ pub async fn create_server(
 
 pub async fn get_server(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<ServerResponse>, AppError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&id)?;
 
     let server: ServerRow = sqlx::query_as(
         "SELECT * FROM servers WHERE id = ?"
@@ -273,16 +369,7 @@ pub async fn get_server(
 
     let pm = &state.process_manager;
     let dir_exists = StdPath::new(&server.working_dir).exists();
-    let is_running = pm.is_running(&server.id);
-    let status = if !dir_exists {
-        "missing"
-    } else if pm.is_installing(&server.id) {
-        if pm.is_auth_required(&server.id) { "auth_required" } else { "installing" }
-    } else if is_running {
-        if pm.is_auth_required(&server.id) { "auth_required" } else { "running" }
-    } else {
-        "stopped"
-    };
+    let (status, _) = server_status(pm, &server.id, dir_exists).await;
     
     let player_rows: Vec<PlayerRow> = sqlx::query_as(
         "SELECT player_name, player_id, player_ip, is_online, last_seen FROM server_players WHERE server_id = ?"
@@ -410,6 +497,10 @@ pub async fn get_server(
 
     let notifications = server.discord_notifications.as_ref()
         .and_then(|n| serde_json::from_str(n).ok());
+    let depends_on = server.dependencies();
+    let (backup_include, backup_exclude) = backup::rules_from_columns(server.backup_include.as_deref(), server.backup_exclude.as_deref());
+    let backup_replication = server.replication_rules();
+    let stop_countdown = server.stop_countdown();
 
     Ok(Json(ServerResponse {
         id: server.id,
@@ -422,8 +513,11 @@ pub async fn get_server(
         min_memory: server.min_memory,
         max_memory: server.max_memory,
         extra_args: server.extra_args,
+        jvm_profile: server.jvm_profile,
+        java_runtime: server.java_runtime,
         config: config_json,
         auto_start: server.auto_start != 0,
+        depends_on,
         created_at: server.created_at,
         updated_at: server.updated_at,
         dir_exists,
@@ -437,12 +531,31 @@ pub async fn get_server(
         backup_frequency: server.backup_frequency as u32,
         backup_max_backups: server.backup_max_backups as u32,
         backup_prefix: server.backup_prefix,
+        backup_mode: server.backup_mode,
+        backup_include,
+        backup_exclude,
+        backup_replication,
         discord_username: server.discord_username,
         discord_avatar: server.discord_avatar,
         discord_webhook_url: server.discord_webhook_url,
         discord_notifications: notifications,
         logs_retention_days: server.logs_retention_days as u32,
         watchdog_enabled: server.watchdog_enabled != 0,
+        stop_command: server.stop_command,
+        stop_timeout: server.stop_timeout as u32,
+        stop_countdown,
+        execution_backend: server.execution_backend,
+        container_image: server.container_image,
+        cpu_limit: server.cpu_limit,
+        memory_limit: server.memory_limit,
+        pids_limit: server.pids_limit.and_then(|p| u32::try_from(p).ok()),
+        io_weight: server.io_weight.and_then(|w| u16::try_from(w).ok()),
+        launch_env: launch::env_from_column(server.launch_env.as_deref()),
+        launch_command: server.launch_command,
+        aot_cache: server.aot_cache != 0,
+        disable_sentry: server.disable_sentry != 0,
+        game_backup_enabled: server.game_backup_enabled != 0,
+        game_backup_frequency: server.game_backup_frequency.and_then(|m| u32::try_from(m).ok()),
         auth_mode: server.auth_mode,
 
         cpu_usage: cpu,
@@ -457,14 +570,36 @@ pub async fn get_server(
 
 pub async fn update_server(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(body): Json<CreateServerRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerEdit)?;
+    auth.require_server(&id)?;
+    validate_backup_mode(body.backup_mode.as_deref())?;
+    // Reject invalid globs now rather than at the next backup
+    backup::BackupFilter::new(
+        body.backup_include.as_deref().unwrap_or_default(),
+        body.backup_exclude.as_deref().unwrap_or_default(),
+    )?;
+    if let Some(rules) = &body.backup_replication {
+        validate_replication_rules(&state.pool, rules).await?;
+    }
+    validate_stop_settings(&body)?;
+    validate_execution(&body)?;
+    validate_java(&state.pool, &body).await?;
+
     let now = Utc::now().to_rfc3339();
     let auto_start = body.auto_start.unwrap_or(false) as i32;
 
     let config_str = body.config.as_ref().map(|c| c.to_string());
     let notifications_str = body.discord_notifications.as_ref().map(|c| c.to_string());
+    let depends_on_str = body.depends_on.as_ref().and_then(|d| serde_json::to_string(d).ok());
+    let backup_include_str = body.backup_include.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let backup_exclude_str = body.backup_exclude.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let backup_replication_str = body.backup_replication.as_ref().and_then(|r| serde_json::to_string(r).ok());
+    let stop_countdown_str = body.stop_countdown.as_ref().and_then(|c| serde_json::to_string(c).ok());
+    let launch_env_str = body.launch_env.as_ref().and_then(|e| serde_json::to_string(e).ok());
 
     let result = sqlx::query(
         "UPDATE servers SET 
@@ -473,16 +608,38 @@ pub async fn update_server(
         backup_frequency = COALESCE(?, backup_frequency),
         backup_max_backups = COALESCE(?, backup_max_backups),
         backup_prefix = COALESCE(?, backup_prefix),
+        backup_mode = COALESCE(?, backup_mode),
+        backup_include = COALESCE(?, backup_include),
+        backup_exclude = COALESCE(?, backup_exclude),
+        backup_replication = COALESCE(?, backup_replication),
         discord_username = COALESCE(?, discord_username),
         discord_avatar = COALESCE(?, discord_avatar),
         discord_webhook_url = COALESCE(?, discord_webhook_url),
         discord_notifications = COALESCE(?, discord_notifications),
         logs_retention_days = COALESCE(?, logs_retention_days),
         watchdog_enabled = COALESCE(?, watchdog_enabled),
+        stop_command = COALESCE(?, stop_command),
+        stop_timeout = COALESCE(?, stop_timeout),
+        stop_countdown = COALESCE(?, stop_countdown),
+        execution_backend = COALESCE(?, execution_backend),
+        container_image = COALESCE(?, container_image),
+        cpu_limit = COALESCE(?, cpu_limit),
+        memory_limit = COALESCE(?, memory_limit),
+        pids_limit = COALESCE(?, pids_limit),
+        io_weight = COALESCE(?, io_weight),
+        launch_command = COALESCE(?, launch_command),
+        launch_env = COALESCE(?, launch_env),
+        aot_cache = COALESCE(?, aot_cache),
+        disable_sentry = COALESCE(?, disable_sentry),
+        game_backup_enabled = COALESCE(?, game_backup_enabled),
+        game_backup_frequency = COALESCE(?, game_backup_frequency),
+        jvm_profile = COALESCE(?, jvm_profile),
+        java_runtime = COALESCE(?, java_runtime),
         auth_mode = COALESCE(?, auth_mode),
         bind_address = COALESCE(?, bind_address),
         port = COALESCE(?, port),
-        nice_level = COALESCE(?, nice_level)
+        nice_level = COALESCE(?, nice_level),
+        depends_on = COALESCE(?, depends_on)
         WHERE id = ?",
     )
     .bind(&body.name)
@@ -500,16 +657,38 @@ pub async fn update_server(
     .bind(body.backup_frequency)
     .bind(body.backup_max_backups)
     .bind(&body.backup_prefix)
+    .bind(&body.backup_mode)
+    .bind(backup_include_str)
+    .bind(backup_exclude_str)
+    .bind(backup_replication_str)
     .bind(&body.discord_username)
     .bind(&body.discord_avatar)
     .bind(&body.discord_webhook_url)
     .bind(notifications_str)
     .bind(body.logs_retention_days)
     .bind(body.watchdog_enabled.map(|b| b as i32))
+    .bind(&body.stop_command)
+    .bind(body.stop_timeout)
+    .bind(stop_countdown_str)
+    .bind(&body.execution_backend)
+    .bind(&body.container_image)
+    .bind(body.cpu_limit)
+    .bind(&body.memory_limit)
+    .bind(body.pids_limit)
+    .bind(body.io_weight)
+    .bind(&body.launch_command)
+    .bind(launch_env_str)
+    .bind(body.aot_cache.map(|b| b as i32))
+    .bind(body.disable_sentry.map(|b| b as i32))
+    .bind(body.game_backup_enabled.map(|b| b as i32))
+    .bind(body.game_backup_frequency)
+    .bind(&body.jvm_profile)
+    .bind(&body.java_runtime)
     .bind(&body.auth_mode)
     .bind(&body.bind_address)
     .bind(body.port)
     .bind(body.nice_level)
+    .bind(depends_on_str)
     .bind(&id)
     .execute(&state.pool)
     .await?;
@@ -573,8 +752,12 @@ pub async fn update_server(
 
 pub async fn delete_server(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerDelete)?;
+    auth.require_server(&id)?;
+
     let server: Option<(String,)> = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.pool)
@@ -584,6 +767,7 @@ pub async fn delete_server(
     if pm.is_running(&id) {
         pm.stop(&id).await?;
     }
+    pm.release_cgroup(&id);
 
     let result = sqlx::query("DELETE FROM servers WHERE id = ?")
         .bind(&id)
@@ -608,6 +792,14 @@ pub async fn delete_server(
     Ok(SuccessResponse::ok())
 }
 
+/// Status shown for a server, and whether a process is active for it.
+/// A server whose directory is gone is "missing" whatever its state.
+async fn server_status(pm: &ProcessManager, id: &str, dir_exists: bool) -> (&'static str, bool) {
+    let state = pm.current_state(id).await.state;
+    let status = if dir_exists { state.as_str() } else { "missing" };
+    (status, state.is_active())
+}
+
 pub async fn get_server_by_id_internal(pool: &DbPool, id: &str) -> Result<ServerRow, AppError> {
     sqlx::query_as("SELECT * FROM servers WHERE id = ?")
         .bind(id)
@@ -684,3 +876,95 @@ async fn load_player_meta(working_dir: &str) -> std::collections::HashMap<String
 
     meta_map
 }
+
+async fn validate_replication_rules(pool: &DbPool, rules: &[ReplicationRule]) -> Result<(), AppError> {
+    for rule in rules {
+        if rule.keep == 0 {
+            return Err(AppError::BadRequest("backups.invalid_replication".into())
+                .with_code(ErrorCode::BackupInvalidDestination));
+        }
+        replication::load_destination(pool, &rule.destination_id).await?;
+    }
+    Ok(())
+}
+
+fn validate_execution(body: &CreateServerRequest) -> Result<(), AppError> {
+    let backend_valid = body.execution_backend.as_deref()
+        .is_none_or(|b| b == container::BACKEND_NATIVE || b == container::BACKEND_DOCKER);
+    let cpu_valid = body.cpu_limit.is_none_or(|cpus| cpus.is_finite() && cpus > 0.0);
+    let memory_valid = body.memory_limit.as_deref().is_none_or(|m| {
+        m.trim().is_empty() || (m.starts_with(|c: char| c.is_ascii_digit()) && parse_memory_to_bytes(m) > 0)
+    });
+    let limits_valid = body.pids_limit != Some(0) && body.io_weight.is_none_or(|w| (1..=10000).contains(&w));
+    if !backend_valid || !cpu_valid || !memory_valid || !limits_valid {
+        return Err(AppError::BadRequest("servers.invalid_execution_settings".into()).with_code(ErrorCode::ValidationFailed));
+    }
+    // An empty command goes back to the launch profile
+    if let Some(command) = body.launch_command.as_deref().filter(|c| !c.trim().is_empty()) {
+        launch::validate_command(command)?;
+    }
+    if let Some(env) = &body.launch_env {
+        launch::validate_env(env)?;
+    }
+    if let Some(args) = &body.extra_args {
+        launch::split_args(args)?;
+    }
+    let auth_mode_valid = body.auth_mode.as_deref().is_none_or(|m| launch::AUTH_MODES.contains(&m));
+    if !auth_mode_valid || body.game_backup_frequency == Some(0) {
+        return Err(AppError::BadRequest("servers.invalid_launch_options".into()).with_code(ErrorCode::ValidationFailed));
+    }
+    Ok(())
+}
+
+/// The Java runtime and the JVM profile must exist, and the profile must suit the server's Java
+/// when it runs on the host; the image provides it for containers
+async fn validate_java(pool: &DbPool, body: &CreateServerRequest) -> Result<(), AppError> {
+    let runtime = match body.java_runtime.as_deref().filter(|r| !r.is_empty()) {
+        Some(id) => Some(runtime::load(pool, id).await?.ok_or_else(runtime::not_found)?),
+        None => None,
+    };
+    let Some(id) = body.jvm_profile.as_deref().filter(|p| !p.is_empty()) else {
+        return Ok(());
+    };
+    let profile = jvm::find(id)
+        .ok_or_else(|| AppError::BadRequest("servers.invalid_jvm_profile".into()).with_code(ErrorCode::ValidationFailed))?;
+    if body.execution_backend.as_deref() == Some(container::BACKEND_DOCKER) {
+        return Ok(());
+    }
+
+    let major = match runtime {
+        Some(runtime) => u32::try_from(runtime.major).ok(),
+        None => {
+            let java = body.java_path.clone().filter(|p| !p.is_empty()).unwrap_or_else(|| "java".to_string());
+            tokio::task::spawn_blocking(move || system::check_java_version(StdPath::new(&java)))
+                .await
+                .ok()
+                .flatten()
+                .and_then(|v| v.major())
+        }
+    };
+    // A Java that cannot be run now may be installed later
+    if let Some(major) = major {
+        if major < profile.min_java {
+            return Err(AppError::BadRequest(format!(
+                "The {} profile needs Java {} or later, found {}", profile.name, profile.min_java, major
+            )).with_code(ErrorCode::ServerJavaUnsupported));
+        }
+    }
+    Ok(())
+}
+
+fn validate_stop_settings(body: &CreateServerRequest) -> Result<(), AppError> {
+    if body.stop_timeout == Some(0) || body.stop_countdown.as_ref().is_some_and(|c| c.contains(&0)) {
+        return Err(AppError::BadRequest("servers.invalid_stop_settings".into()).with_code(ErrorCode::ValidationFailed));
+    }
+    Ok(())
+}
+
+fn validate_backup_mode(mode: Option<&str>) -> Result<(), AppError> {
+    match mode {
+        Some(m) if !backup::is_valid_kind(m) => Err(AppError::BadRequest("backups.invalid_mode".into())
+            .with_code(ErrorCode::BackupInvalidMode)),
+        _ => Ok(()),
+    }
+}
@@ -11,6 +11,8 @@ use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
 
 use crate::core::{AppState, error::AppError};
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::api::servers::models::{
     FileEntry, FilesQuery, ReadFileQuery, WriteFileRequest, DeleteFileRequest, 
@@ -20,9 +22,12 @@ use crate::utils::files::{calculate_dir_size, ensure_within_base, copy_dir_recur
 
 pub async fn list_server_files(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Query(query): Query<FilesQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesRead)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -121,9 +126,12 @@ pub async fn list_server_files(
 
 pub async fn read_server_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Query(query): Query<ReadFileQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesRead)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -175,9 +183,12 @@ pub async fn read_server_file(
 
 pub async fn write_server_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<WriteFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -200,9 +211,12 @@ pub async fn write_server_file(
 
 pub async fn delete_server_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<DeleteFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesDelete)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -233,9 +247,12 @@ pub async fn delete_server_file(
 
 pub async fn create_folder(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<CreateFolderRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -262,9 +279,12 @@ pub async fn create_folder(
 
 pub async fn create_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<CreateFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -296,9 +316,12 @@ pub async fn create_file(
 
 pub async fn upload_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     mut multipart: Multipart,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -356,9 +379,12 @@ pub async fn upload_file(
 
 pub async fn download_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Query(query): Query<ReadFileQuery>,
 ) -> Result<Response<Body>, AppError> {
+    auth.require(Permission::ServerFilesRead)?;
+    auth.require_server(&server_id)?;
     
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
@@ -400,9 +426,13 @@ pub async fn download_file(
 
 pub async fn rename_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<RenameFileRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
+
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
         .fetch_optional(&state.pool)
@@ -435,9 +465,13 @@ pub async fn rename_file(
 
 pub async fn copy_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<CopyFileRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
+
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
         .fetch_optional(&state.pool)
@@ -469,9 +503,13 @@ pub async fn copy_file(
 
 pub async fn move_file(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<MoveFileRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::ServerFilesWrite)?;
+    auth.require_server(&server_id)?;
+
     let server: (String,) = sqlx::query_as("SELECT working_dir FROM servers WHERE id = ?")
         .bind(&server_id)
         .fetch_optional(&state.pool)
@@ -8,6 +8,7 @@ use std::path::Path as StdPath;
 use tokio::fs;
 use crate::{core::error::AppError as ApiError, core::AppState};
 use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use super::crud::get_server_by_id_internal;
 
 // ================= MODELS =================
@@ -108,9 +109,12 @@ fn get_player_file_path(working_dir: &str, filename: &str) -> std::path::PathBuf
 
 pub async fn get_whitelist(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&id)?;
+
     let list = get_whitelist_internal(&state.pool, &id).await?;
     Ok(Json(list))
 }
@@ -163,10 +167,13 @@ pub async fn get_whitelist_internal(pool: &crate::core::database::DbPool, id: &s
 
 pub async fn add_whitelist(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<AddWhitelistRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "whitelist.json");
 
@@ -227,10 +234,13 @@ pub async fn add_whitelist(
 
 pub async fn remove_whitelist(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<RemoveWhitelistRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "whitelist.json");
     
@@ -281,9 +291,12 @@ pub async fn remove_whitelist(
 
 pub async fn get_bans(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "bans.json");
     
@@ -306,10 +319,13 @@ pub async fn get_bans(
 
 pub async fn add_ban(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<AddBanRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "bans.json");
     
@@ -345,9 +361,12 @@ pub async fn add_ban(
 
 pub async fn get_ops(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "permissions.json");
 
@@ -384,10 +403,13 @@ pub async fn get_ops(
 
 pub async fn add_op(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<AddOpRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "permissions.json");
 
@@ -419,10 +441,13 @@ pub async fn add_op(
 
 pub async fn remove_op(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<AddOpRequest>, 
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "permissions.json");
 
@@ -441,10 +466,13 @@ pub async fn remove_op(
 
 pub async fn remove_ban(
     State(state): State<AppState>,
-    _auth: AuthUser,
+    auth: AuthUser,
     Path(id): Path<String>,
     Json(payload): Json<AddBanRequest>, 
 ) -> Result<impl IntoResponse, ApiError> {
+    auth.require(Permission::ServerPlayersManage)?;
+    auth.require_server(&id)?;
+
     let server = get_server_by_id_internal(&state.pool, &id).await?;
     let path = get_player_file_path(&server.working_dir, "bans.json");
 
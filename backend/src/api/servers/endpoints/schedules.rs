@@ -7,14 +7,20 @@ use chrono::Utc;
 use uuid::Uuid;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
 use crate::api::servers::models::{ScheduleRow, ScheduleResponse, CreateScheduleRequest, ToggleScheduleRequest};
 
 pub async fn list_schedules(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
 ) -> Result<Json<Vec<ScheduleResponse>>, AppError> {
+    auth.require(Permission::ServerView)?;
+    auth.require_server(&server_id)?;
+
     let schedules: Vec<ScheduleRow> = sqlx::query_as(
         "SELECT * FROM schedules WHERE server_id = ? ORDER BY created_at DESC"
     )
@@ -42,9 +48,13 @@ pub async fn list_schedules(
 
 pub async fn create_schedule(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(server_id): Path<String>,
     Json(body): Json<CreateScheduleRequest>,
 ) -> Result<(StatusCode, Json<ScheduleResponse>), AppError> {
+    auth.require(Permission::ServerSchedulesManage)?;
+    auth.require_server(&server_id)?;
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
@@ -86,13 +96,17 @@ pub async fn create_schedule(
 
 pub async fn update_schedule(
     State(state): State<AppState>,
-    Path((_server_id, schedule_id)): Path<(String, String)>,
+    auth: AuthUser,
+    Path((server_id, schedule_id)): Path<(String, String)>,
     Json(body): Json<CreateScheduleRequest>,
 ) -> Result<Json<ScheduleResponse>, AppError> {
+    auth.require(Permission::ServerSchedulesManage)?;
+    auth.require_server(&server_id)?;
+
     sqlx::query(
         "UPDATE schedules SET 
         name = ?, task_type = ?, action = ?, interval = ?, unit = ?, time = ?, cron_expression = ?, enabled = ?, delete_after = ?
-        WHERE id = ?"
+        WHERE id = ? AND server_id = ?"
     )
     .bind(&body.name)
     .bind(&body.task_type)
@@ -104,13 +118,16 @@ pub async fn update_schedule(
     .bind(body.enabled.unwrap_or(true) as i32)
     .bind(body.delete_after.unwrap_or(false) as i32)
     .bind(&schedule_id)
+    .bind(&server_id)
     .execute(&state.pool)
     .await?;
 
-    let s: ScheduleRow = sqlx::query_as("SELECT * FROM schedules WHERE id = ?")
+    let s: ScheduleRow = sqlx::query_as("SELECT * FROM schedules WHERE id = ? AND server_id = ?")
         .bind(&schedule_id)
-        .fetch_one(&state.pool)
-        .await?;
+        .bind(&server_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("schedules.not_found".into()))?;
 
     Ok(Json(ScheduleResponse {
         id: s.id,
@@ -130,10 +147,15 @@ pub async fn update_schedule(
 
 pub async fn delete_schedule(
     State(state): State<AppState>,
-    Path((_server_id, schedule_id)): Path<(String, String)>,
+    auth: AuthUser,
+    Path((server_id, schedule_id)): Path<(String, String)>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    sqlx::query("DELETE FROM schedules WHERE id = ?")
+    auth.require(Permission::ServerSchedulesManage)?;
+    auth.require_server(&server_id)?;
+
+    sqlx::query("DELETE FROM schedules WHERE id = ? AND server_id = ?")
         .bind(&schedule_id)
+        .bind(&server_id)
         .execute(&state.pool)
         .await?;
 
@@ -142,12 +164,17 @@ pub async fn delete_schedule(
 
 pub async fn toggle_schedule(
     State(state): State<AppState>,
-    Path((_server_id, schedule_id)): Path<(String, String)>,
+    auth: AuthUser,
+    Path((server_id, schedule_id)): Path<(String, String)>,
     Json(body): Json<ToggleScheduleRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    sqlx::query("UPDATE schedules SET enabled = ? WHERE id = ?")
+    auth.require(Permission::ServerSchedulesManage)?;
+    auth.require_server(&server_id)?;
+
+    sqlx::query("UPDATE schedules SET enabled = ? WHERE id = ? AND server_id = ?")
         .bind(body.enabled as i32)
         .bind(&schedule_id)
+        .bind(&server_id)
         .execute(&state.pool)
         .await?;
 
@@ -156,12 +183,18 @@ pub async fn toggle_schedule(
 
 pub async fn run_schedule(
     State(state): State<AppState>,
-    Path((_server_id, schedule_id)): Path<(String, String)>,
+    auth: AuthUser,
+    Path((server_id, schedule_id)): Path<(String, String)>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    let s: ScheduleRow = sqlx::query_as("SELECT * FROM schedules WHERE id = ?")
+    auth.require(Permission::ServerSchedulesManage)?;
+    auth.require_server(&server_id)?;
+
+    let s: ScheduleRow = sqlx::query_as("SELECT * FROM schedules WHERE id = ? AND server_id = ?")
         .bind(&schedule_id)
-        .fetch_one(&state.pool)
-        .await?;
+        .bind(&server_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("schedules.not_found".into()))?;
 
     let srv: crate::api::servers::models::ServerRow = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
         .bind(&s.server_id)
@@ -183,7 +216,9 @@ pub async fn run_schedule(
                 srv.extra_args.as_deref(),
                 config_json.as_ref(),
                 &srv.game_type,
-                srv.nice_level
+                srv.nice_level,
+                srv.container_settings().as_ref(),
+                &srv.resource_limits(),
             ).await; 
         },
         "stop" => { let _ = pm.stop(&s.server_id).await; },
@@ -198,24 +233,13 @@ pub async fn run_schedule(
                 srv.extra_args.as_deref(),
                 config_json.as_ref(),
                 &srv.game_type,
-                srv.nice_level
+                srv.nice_level,
+                srv.container_settings().as_ref(),
+                &srv.resource_limits(),
             ).await; 
         },
         "backup" => {
-            let filename = format!("backup_{}_{}.tar.gz", s.server_id, Utc::now().format("%Y%m%d_%H%M%S"));
-            let backup_path = format!("backups/{filename}");
-            
-            // Fix: correctly call async create_archive
-            if let Ok(size) = crate::services::system::backup::create_archive(srv.working_dir.clone(), backup_path.clone()).await {
-                    let _ = sqlx::query("INSERT INTO backups (id, server_id, filename, size_bytes, created_at) VALUES (?, ?, ?, ?, ?)")
-                    .bind(uuid::Uuid::new_v4().to_string())
-                    .bind(&s.server_id)
-                    .bind(&filename)
-                    .bind(size as i64)
-                    .bind(Utc::now().to_rfc3339())
-                    .execute(&state.pool)
-                    .await;
-            }
+            crate::services::system::scheduler::run_automatic_backup(&state.pool, pm, &srv).await?;
         },
         _ => {}
     }
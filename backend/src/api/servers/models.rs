@@ -1,6 +1,17 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::core::error::AppError;
+use crate::services::game::cgroup::ResourceLimits;
+use crate::services::game::jvm::{self, JvmProfile};
+use crate::services::game::launch::HytaleOptions;
+use crate::services::game::container::{ContainerSettings, BACKEND_DOCKER, DEFAULT_IMAGE};
+use crate::services::system::backup::BackupFilter;
+use crate::services::system::replication::{self, ReplicationRule};
+use crate::utils::memory::parse_memory_to_bytes;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateServerRequest {
     pub name: String,
@@ -8,23 +19,62 @@ pub struct CreateServerRequest {
     pub executable_path: String,
     pub working_dir: String,
     pub java_path: Option<String>,
+    /// Id of an installed Java runtime, used instead of `java_path`; empty to go back to it
+    pub java_runtime: Option<String>,
     pub min_memory: Option<String>,
     pub max_memory: Option<String>,
+    /// Extra JVM arguments, quoted like in a shell
     pub extra_args: Option<String>,
+    /// Id of a JVM tuning profile, e.g. "g1"; empty for the default sizing and no flags
+    pub jvm_profile: Option<String>,
     pub config: Option<serde_json::Value>,
     pub auto_start: Option<bool>,
+    pub depends_on: Option<Vec<String>>,
     
     // New fields
     pub backup_enabled: Option<bool>,
     pub backup_frequency: Option<u32>,
     pub backup_max_backups: Option<u32>,
     pub backup_prefix: Option<String>,
+    pub backup_mode: Option<String>,
+    pub backup_include: Option<Vec<String>>,
+    pub backup_exclude: Option<Vec<String>>,
+    pub backup_replication: Option<Vec<ReplicationRule>>,
     pub discord_username: Option<String>,
     pub discord_avatar: Option<String>,
     pub discord_webhook_url: Option<String>,
     pub discord_notifications: Option<serde_json::Value>,
     pub logs_retention_days: Option<u32>,
     pub watchdog_enabled: Option<bool>,
+    /// Console command sent to stop the server; the game's default when unset
+    pub stop_command: Option<String>,
+    /// Seconds to wait for the process to exit before it is killed
+    pub stop_timeout: Option<u32>,
+    /// Seconds before the stop at which players are warned, e.g. [60, 30, 10]
+    pub stop_countdown: Option<Vec<u32>>,
+    /// "native" (host process) or "docker"
+    pub execution_backend: Option<String>,
+    /// Image of containerized servers; a Java image by default
+    pub container_image: Option<String>,
+    /// CPUs the server may use, e.g. 1.5
+    pub cpu_limit: Option<f64>,
+    /// Memory the whole process may use, e.g. "10G"; the heap plus its overhead by default
+    pub memory_limit: Option<String>,
+    pub pids_limit: Option<u32>,
+    /// IO weight relative to the other servers, from 1 to 10000 (100 by default)
+    pub io_weight: Option<u16>,
+    /// Command line template replacing the launch profile of the game type, e.g. "{java} -jar {jar}"
+    pub launch_command: Option<String>,
+    /// Environment variables added to the launch profile's
+    pub launch_env: Option<HashMap<String, String>>,
+    /// Hytale: use HytaleServer.aot when it is present (on by default)
+    pub aot_cache: Option<bool>,
+    /// Hytale: do not send crash reports to Sentry
+    pub disable_sentry: Option<bool>,
+    /// Hytale: the server's own world backups
+    pub game_backup_enabled: Option<bool>,
+    /// Minutes between two of the server's own backups
+    pub game_backup_frequency: Option<u32>,
     
     // Server settings
     pub auth_mode: Option<String>,
@@ -54,11 +104,14 @@ pub struct ServerResponse {
     pub executable_path: String,
     pub working_dir: String,
     pub java_path: Option<String>,
+    pub java_runtime: Option<String>,
     pub min_memory: Option<String>,
     pub max_memory: Option<String>,
     pub extra_args: Option<String>,
+    pub jvm_profile: Option<String>,
     pub config: Option<serde_json::Value>,
     pub auto_start: bool,
+    pub depends_on: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
     pub dir_exists: bool,
@@ -73,12 +126,31 @@ pub struct ServerResponse {
     pub backup_frequency: u32,
     pub backup_max_backups: u32,
     pub backup_prefix: String,
+    pub backup_mode: String,
+    pub backup_include: Vec<String>,
+    pub backup_exclude: Vec<String>,
+    pub backup_replication: Vec<ReplicationRule>,
     pub discord_username: Option<String>,
     pub discord_avatar: Option<String>,
     pub discord_webhook_url: Option<String>,
     pub discord_notifications: Option<serde_json::Value>,
     pub logs_retention_days: u32,
     pub watchdog_enabled: bool,
+    pub stop_command: Option<String>,
+    pub stop_timeout: u32,
+    pub stop_countdown: Vec<u32>,
+    pub execution_backend: String,
+    pub container_image: Option<String>,
+    pub cpu_limit: Option<f64>,
+    pub memory_limit: Option<String>,
+    pub pids_limit: Option<u32>,
+    pub io_weight: Option<u16>,
+    pub launch_command: Option<String>,
+    pub launch_env: HashMap<String, String>,
+    pub aot_cache: bool,
+    pub disable_sentry: bool,
+    pub game_backup_enabled: bool,
+    pub game_backup_frequency: Option<u32>,
     pub auth_mode: String,
 
     pub cpu_usage: f32,
@@ -130,6 +202,14 @@ pub struct ServerRow {
     #[sqlx(default)]
     pub backup_prefix: String,
     #[sqlx(default)]
+    pub backup_mode: String,
+    #[sqlx(default)]
+    pub backup_include: Option<String>,
+    #[sqlx(default)]
+    pub backup_exclude: Option<String>,
+    #[sqlx(default)]
+    pub backup_replication: Option<String>,
+    #[sqlx(default)]
     pub discord_username: Option<String>,
     #[sqlx(default)]
     pub discord_avatar: Option<String>,
@@ -142,6 +222,40 @@ pub struct ServerRow {
     #[sqlx(default)]
     pub watchdog_enabled: i32,
     #[sqlx(default)]
+    pub stop_command: Option<String>,
+    #[sqlx(default)]
+    pub stop_timeout: i32,
+    #[sqlx(default)]
+    pub stop_countdown: Option<String>,
+    #[sqlx(default)]
+    pub execution_backend: String,
+    #[sqlx(default)]
+    pub container_image: Option<String>,
+    #[sqlx(default)]
+    pub cpu_limit: Option<f64>,
+    #[sqlx(default)]
+    pub memory_limit: Option<String>,
+    #[sqlx(default)]
+    pub pids_limit: Option<i64>,
+    #[sqlx(default)]
+    pub io_weight: Option<i64>,
+    #[sqlx(default)]
+    pub launch_command: Option<String>,
+    #[sqlx(default)]
+    pub launch_env: Option<String>,
+    #[sqlx(default)]
+    pub aot_cache: i32,
+    #[sqlx(default)]
+    pub disable_sentry: i32,
+    #[sqlx(default)]
+    pub game_backup_enabled: i32,
+    #[sqlx(default)]
+    pub game_backup_frequency: Option<i64>,
+    #[sqlx(default)]
+    pub jvm_profile: Option<String>,
+    #[sqlx(default)]
+    pub java_runtime: Option<String>,
+    #[sqlx(default)]
     pub auth_mode: String,
     #[sqlx(default)]
     pub bind_address: String,
@@ -149,6 +263,72 @@ pub struct ServerRow {
     pub port: i32,
     #[sqlx(default)]
     pub nice_level: i32,
+    #[sqlx(default)]
+    pub depends_on: Option<String>,
+}
+
+impl ServerRow {
+    pub fn backup_filter(&self) -> Result<BackupFilter, AppError> {
+        BackupFilter::from_columns(self.backup_include.as_deref(), self.backup_exclude.as_deref())
+    }
+
+    /// Remote destinations this server's backups are copied to
+    pub fn replication_rules(&self) -> Vec<ReplicationRule> {
+        replication::rules_from_column(self.backup_replication.as_deref())
+    }
+
+    /// Seconds before a stop at which players are warned
+    pub fn stop_countdown(&self) -> Vec<u32> {
+        countdown_from_column(self.stop_countdown.as_deref())
+    }
+
+    /// How the server is run in a container; `None` for host processes
+    pub fn container_settings(&self) -> Option<ContainerSettings> {
+        (self.execution_backend == BACKEND_DOCKER).then(|| ContainerSettings {
+            image: self.container_image.clone()
+                .filter(|i| !i.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_IMAGE.to_string()),
+        })
+    }
+
+    pub fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            memory_bytes: self.memory_limit.as_deref()
+                .filter(|m| !m.trim().is_empty())
+                .map(parse_memory_to_bytes),
+            cpus: self.cpu_limit,
+            pids: self.pids_limit.and_then(|p| u32::try_from(p).ok()),
+            io_weight: self.io_weight.and_then(|w| u16::try_from(w).ok()),
+        }
+    }
+
+    pub fn hytale_options(&self) -> HytaleOptions {
+        HytaleOptions {
+            auth_mode: self.auth_mode.clone(),
+            aot_cache: self.aot_cache != 0,
+            disable_sentry: self.disable_sentry != 0,
+            backup: self.game_backup_enabled != 0,
+            backup_frequency: self.game_backup_frequency.and_then(|m| u32::try_from(m).ok()),
+        }
+    }
+
+    /// An unknown id, left from an older version, counts as none
+    pub fn jvm_profile(&self) -> Option<&'static JvmProfile> {
+        self.jvm_profile.as_deref().and_then(jvm::find)
+    }
+
+    /// Servers that must be started before this one when the panel boots
+    pub fn dependencies(&self) -> Vec<String> {
+        self.depends_on.as_ref()
+            .and_then(|d| serde_json::from_str(d).ok())
+            .unwrap_or_default()
+    }
+}
+
+pub fn countdown_from_column(column: Option<&str>) -> Vec<u32> {
+    column
+        .and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or_default()
 }
 
 // ============= Server Files API Models =============
@@ -267,3 +447,15 @@ pub struct CreateScheduleRequest {
 pub struct ToggleScheduleRequest {
     pub enabled: bool,
 }
+
+// ============= Crash History Models =============
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CrashRow {
+    pub id: String,
+    pub server_id: String,
+    pub exit_code: Option<i32>,
+    pub log_tail: Option<String>,
+    pub restarted: bool,
+    pub crashed_at: String,
+}
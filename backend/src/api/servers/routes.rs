@@ -19,7 +19,9 @@ pub fn routes() -> Router<AppState> {
         .route("/:id/restart", post(lifecycle::restart_server))
         .route("/:id/kill", post(lifecycle::kill_server))
         .route("/:id/reinstall", post(lifecycle::reinstall_server))
+        .route("/:id/crashes", get(lifecycle::list_crashes))
         .route("/:id/command", post(console::send_command))
+        .route("/:id/console/history", get(console::console_history))
         
         // Files API
         .route("/:id/files", get(files::list_server_files))
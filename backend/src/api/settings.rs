@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use tracing::error;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::core::database::upsert_setting;
 use crate::core::error::AppError;
 use crate::api::SuccessResponse;
@@ -14,6 +16,7 @@ use crate::api::SuccessResponse;
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(get_settings).put(update_settings))
+        .route("/login", get(get_login_settings))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +29,14 @@ pub struct SettingsResponse {
     pub is_docker: bool,
     pub login_default_color: Option<String>,
     pub login_background_url: Option<String>,
+    pub autostart_delay_seconds: u64,
+}
+
+/// Branding of the login page, readable before signing in
+#[derive(Debug, Serialize)]
+pub struct LoginSettingsResponse {
+    pub login_default_color: Option<String>,
+    pub login_background_url: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -36,9 +47,12 @@ struct UpdateSettingsRequest {
     database_path: Option<String>,
     login_default_color: Option<String>,
     login_background_url: Option<String>,
+    autostart_delay_seconds: Option<u64>,
 }
 
-async fn get_settings(State(state): State<AppState>) -> Result<Json<SettingsResponse>, AppError> {
+async fn get_settings(State(state): State<AppState>, auth: AuthUser) -> Result<Json<SettingsResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
     // Read from DB
     let settings_rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
         .fetch_all(&state.pool)
@@ -53,29 +67,42 @@ async fn get_settings(State(state): State<AppState>) -> Result<Json<SettingsResp
         .or_else(|| settings_map.get("servers_dir").cloned())
         .unwrap_or_else(|| "./data/servers".into());
     
-    let backups_dir = std::env::var("BACKUPS_DIR")
-        .ok()
-        .or_else(|| settings_map.get("backups_dir").cloned())
-        .unwrap_or_else(|| "./data/backups".into());
-
     let settings = SettingsResponse {
         version: env!("CARGO_PKG_VERSION").to_string(),
         servers_dir,
-        backups_dir,
+        backups_dir: backups_dir(&settings_map),
         database_path: std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:data/database.db".into()),
         webhook_url: settings_map.get("webhook_url").cloned(),
         is_docker: std::env::var("IS_DOCKER").is_ok(),
         login_default_color: settings_map.get("login_default_color").cloned(),
         login_background_url: settings_map.get("login_background_url").cloned(),
+        autostart_delay_seconds: autostart_delay_seconds(&settings_map),
     };
 
     Ok(Json(settings))
 }
 
+async fn get_login_settings(State(state): State<AppState>) -> Result<Json<LoginSettingsResponse>, AppError> {
+    let settings_rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('login_default_color', 'login_background_url')"
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    let mut settings_map: std::collections::HashMap<String, String> = settings_rows.into_iter().collect();
+
+    Ok(Json(LoginSettingsResponse {
+        login_default_color: settings_map.remove("login_default_color"),
+        login_background_url: settings_map.remove("login_background_url"),
+    }))
+}
+
 async fn update_settings(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<UpdateSettingsRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
     if let Some(ref webhook_url) = body.webhook_url {
         upsert_setting(&state.pool, "webhook_url", webhook_url).await?;
     }
@@ -104,9 +131,30 @@ async fn update_settings(
         upsert_setting(&state.pool, "login_background_url", url).await?;
     }
 
+    if let Some(delay) = body.autostart_delay_seconds {
+        upsert_setting(&state.pool, "autostart_delay_seconds", &delay.to_string()).await?;
+    }
+
     Ok(SuccessResponse::with_message("Settings updated successfully"))
 }
 
+/// Where backups are stored. Priority: Env > DB > Default. Read once at startup.
+pub fn backups_dir(settings_map: &std::collections::HashMap<String, String>) -> String {
+    std::env::var("BACKUPS_DIR")
+        .ok()
+        .or_else(|| settings_map.get("backups_dir").cloned())
+        .unwrap_or_else(|| crate::services::system::backup::DEFAULT_BACKUPS_DIR.into())
+}
+
+/// Delay between two servers started on boot. Priority: Env > DB > Default
+pub fn autostart_delay_seconds(settings_map: &std::collections::HashMap<String, String>) -> u64 {
+    std::env::var("AUTOSTART_DELAY")
+        .ok()
+        .or_else(|| settings_map.get("autostart_delay_seconds").cloned())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10)
+}
+
 async fn update_env_file(key: &str, value: &str) -> std::io::Result<()> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
     
@@ -11,7 +11,10 @@ use walkdir::WalkDir;
 use tokio::sync::RwLock;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::core::error::AppError;
+use crate::services::game::jvm::{self, JvmProfile};
 
 #[derive(Debug, Serialize)]
 pub struct SystemStatsResponse {
@@ -37,6 +40,12 @@ pub struct JavaVersion {
     pub version: String,
 }
 
+impl JavaVersion {
+    pub fn major(&self) -> Option<u32> {
+        jvm::major_version(&self.version)
+    }
+}
+
 // Keep a static System instance for accurate CPU readings
 lazy_static::lazy_static! {
     static ref SYSTEM: Arc<Mutex<System>> = Arc::new(Mutex::new(System::new_all()));
@@ -76,9 +85,30 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/stats", get(get_system_stats))
         .route("/java-versions", get(get_java_versions))
+        .route("/jvm-profiles", get(get_jvm_profiles))
 }
 
-async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
+async fn get_java_versions(auth: AuthUser) -> Result<Json<Vec<JavaVersion>>, AppError> {
+    // Needed by both the create and the edit server forms
+    if !auth.has_permission(Permission::ServerCreate) {
+        auth.require(Permission::ServerEdit)?;
+    }
+
+    let versions = tokio::task::spawn_blocking(detect_java_versions)
+        .await
+        .map_err(|e| AppError::Internal(format!("Java detection failed: {e}")))?;
+    Ok(Json(versions))
+}
+
+async fn get_jvm_profiles(auth: AuthUser) -> Result<Json<&'static [JvmProfile]>, AppError> {
+    if !auth.has_permission(Permission::ServerCreate) {
+        auth.require(Permission::ServerEdit)?;
+    }
+    Ok(Json(&jvm::PROFILES))
+}
+
+/// Java installations found from JAVA_HOME, the PATH and the usual install directories
+pub fn detect_java_versions() -> Vec<JavaVersion> {
     let mut versions = Vec::new();
     let mut checked_paths = std::collections::HashSet::new();
 
@@ -140,10 +170,10 @@ async fn get_java_versions() -> Result<Json<Vec<JavaVersion>>, AppError> {
         }
     }
 
-    Ok(Json(versions))
+    versions
 }
 
-fn check_java_version(path: &std::path::Path) -> Option<JavaVersion> {
+pub fn check_java_version(path: &std::path::Path) -> Option<JavaVersion> {
     let output = Command::new(path).arg("-version").output().ok()?;
     let output_str = String::from_utf8_lossy(&output.stderr);
     for line in output_str.lines() {
@@ -160,7 +190,12 @@ fn check_java_version(path: &std::path::Path) -> Option<JavaVersion> {
     None
 }
 
-async fn get_system_stats(State(state): State<AppState>) -> Result<Json<SystemStatsResponse>, AppError> {
+async fn get_system_stats(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<SystemStatsResponse>, AppError> {
+    auth.require(Permission::ServerView)?;
+
     let pm = &state.process_manager;
     let (cpu_usage, ram_percent, ram_used, ram_total, cpu_cores) = get_cached_system_stats().await;
 
@@ -6,13 +6,20 @@ use axum::{
 use uuid::Uuid;
 use std::io::Write;
 use crate::core::{AppState, AppError};
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/image", post(upload_image))
 }
 
-async fn upload_image(mut multipart: Multipart) -> Result<Json<serde_json::Value>, AppError> {
+async fn upload_image(
+    auth: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
     // Create uploads directory if it doesn't exist
     let upload_dir_str = std::env::var("UPLOADS_DIR").unwrap_or_else(|_| "./data/uploads".into());
     let upload_dir = std::path::Path::new(&upload_dir_str);
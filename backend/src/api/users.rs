@@ -10,6 +10,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 
 use crate::core::AppState;
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 use crate::api::SuccessResponse;
 use crate::core::error::AppError;
 
@@ -60,7 +62,12 @@ pub struct UpdateUserRequest {
     pub must_change_password: Option<bool>,
 }
 
-async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+async fn list_users(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<serde_json::Value>>, AppError> {
+    auth.require(Permission::UsersManage)?;
+
     let users: Vec<UserResponse> = sqlx::query_as(
         r#"SELECT id, username, role, 
            COALESCE(is_active, 1) as is_active,
@@ -105,8 +112,11 @@ async fn list_users(State(state): State<AppState>) -> Result<Json<Vec<serde_json
 
 async fn get_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(user_id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::UsersManage)?;
+
     let user: UserResponse = sqlx::query_as(
         r#"SELECT id, username, role,
            COALESCE(is_active, 1) as is_active,
@@ -158,8 +168,11 @@ fn validate_username(username: &str) -> Result<(), AppError> {
 
 async fn create_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require(Permission::UsersManage)?;
+
     validate_username(&body.username)?;
 
     // Check if username already exists
@@ -230,9 +243,12 @@ async fn create_user(
 
 async fn update_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(user_id): Path<String>,
     Json(body): Json<UpdateUserRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::UsersManage)?;
+
     let now = Utc::now().to_rfc3339();
 
     // Check if user exists
@@ -338,8 +354,11 @@ async fn update_user(
 
 async fn delete_user(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(user_id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    auth.require(Permission::UsersManage)?;
+
     let result = sqlx::query("DELETE FROM users WHERE id = ?")
         .bind(&user_id)
         .execute(&state.pool)
@@ -6,6 +6,8 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::core::{AppState, AppError};
+use crate::api::auth::AuthUser;
+use crate::core::permissions::Permission;
 
 pub fn routes() -> Router<AppState> {
     Router::new()
@@ -32,8 +34,11 @@ pub struct TestWebhookRequest {
 
 async fn test_webhook(
     State(state): State<AppState>,
+    auth: AuthUser,
     Json(body): Json<TestWebhookRequest>,
 ) -> Result<Json<WebhookTestResponse>, AppError> {
+    auth.require(Permission::SettingsManage)?;
+
     let pool = &state.pool;
     let pm = &state.process_manager;
 
@@ -2,6 +2,9 @@ use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
 use std::io::Error;
 use tracing::info;
 use chrono::Utc;
+use crate::services::game::launch::{
+    BUILTIN_PROFILES, HYTALE_COMMAND, HYTALE_COMMAND_V1, MINECRAFT_COMMAND, MINECRAFT_COMMAND_V1,
+};
 
 pub type DbPool = Pool<Sqlite>;
 
@@ -139,6 +142,10 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             backup_frequency INTEGER NOT NULL DEFAULT 30,
             backup_max_backups INTEGER NOT NULL DEFAULT 7,
             backup_prefix TEXT NOT NULL DEFAULT 'hytale_backup',
+            backup_mode TEXT NOT NULL DEFAULT 'archive',
+            backup_include TEXT,
+            backup_exclude TEXT,
+            backup_replication TEXT,
             
             discord_username TEXT DEFAULT 'Hytale Bot',
             discord_avatar TEXT DEFAULT '',
@@ -147,11 +154,29 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             
             logs_retention_days INTEGER NOT NULL DEFAULT 7,
             watchdog_enabled INTEGER NOT NULL DEFAULT 1,
+            stop_command TEXT,
+            stop_timeout INTEGER NOT NULL DEFAULT 30,
+            stop_countdown TEXT,
+            execution_backend TEXT NOT NULL DEFAULT 'native',
+            container_image TEXT,
+            cpu_limit REAL,
+            memory_limit TEXT,
+            pids_limit INTEGER,
+            io_weight INTEGER,
+            launch_command TEXT,
+            launch_env TEXT,
+            aot_cache INTEGER NOT NULL DEFAULT 1,
+            disable_sentry INTEGER NOT NULL DEFAULT 0,
+            game_backup_enabled INTEGER NOT NULL DEFAULT 0,
+            game_backup_frequency INTEGER,
+            jvm_profile TEXT,
+            java_runtime TEXT,
             
             auth_mode TEXT NOT NULL DEFAULT 'authenticated',
             bind_address TEXT NOT NULL DEFAULT '0.0.0.0',
             port INTEGER NOT NULL DEFAULT 5520,
-            nice_level INTEGER NOT NULL DEFAULT 0
+            nice_level INTEGER NOT NULL DEFAULT 0,
+            depends_on TEXT
         );
 
         CREATE TABLE IF NOT EXISTS backups (
@@ -159,10 +184,54 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
             server_id TEXT NOT NULL,
             filename TEXT NOT NULL,
             size_bytes INTEGER NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'archive',
+            checksum TEXT,
+            verify_status TEXT, -- ok, corrupted
+            verify_error TEXT,
+            verified_at TEXT,
+            origin TEXT NOT NULL DEFAULT 'manual', -- manual, scheduled, import, replica, pre_restore
             created_at TEXT NOT NULL,
             FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
         );
 
+        CREATE TABLE IF NOT EXISTS launch_profiles (
+            game_type TEXT PRIMARY KEY,
+            command TEXT NOT NULL,
+            env TEXT,
+            stop_command TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS java_runtimes (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            version TEXT NOT NULL,
+            major INTEGER NOT NULL,
+            java_path TEXT NOT NULL,
+            source TEXT NOT NULL,
+            sha256 TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS backup_destinations (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            config TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS backup_replicas (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            destination_id TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE,
+            FOREIGN KEY (destination_id) REFERENCES backup_destinations(id) ON DELETE CASCADE
+        );
+
         CREATE TABLE IF NOT EXISTS schedules (
             id TEXT PRIMARY KEY,
             server_id TEXT NOT NULL,
@@ -210,6 +279,16 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
 
         CREATE INDEX IF NOT EXISTS idx_metrics_server_recorded ON server_metrics(server_id, recorded_at);
 
+        CREATE TABLE IF NOT EXISTS server_crashes (
+            id TEXT PRIMARY KEY,
+            server_id TEXT NOT NULL,
+            exit_code INTEGER,
+            log_tail TEXT,
+            restarted INTEGER NOT NULL DEFAULT 0,
+            crashed_at TEXT NOT NULL,
+            FOREIGN KEY (server_id) REFERENCES servers(id) ON DELETE CASCADE
+        );
+
         CREATE TABLE IF NOT EXISTS app_secrets (
             key TEXT PRIMARY KEY,
             value TEXT NOT NULL,
@@ -318,6 +397,101 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
     if !server_column_names.contains(&"nice_level") {
         sqlx::query("ALTER TABLE servers ADD COLUMN nice_level INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
     }
+    if !server_column_names.contains(&"depends_on") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN depends_on TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_mode") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_mode TEXT NOT NULL DEFAULT 'archive'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_include") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_include TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_exclude") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_exclude TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"backup_replication") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN backup_replication TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"stop_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN stop_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"stop_timeout") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN stop_timeout INTEGER NOT NULL DEFAULT 30").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"stop_countdown") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN stop_countdown TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"execution_backend") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN execution_backend TEXT NOT NULL DEFAULT 'native'").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"container_image") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN container_image TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"cpu_limit") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN cpu_limit REAL").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"memory_limit") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN memory_limit TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"pids_limit") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN pids_limit INTEGER").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"io_weight") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN io_weight INTEGER").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"launch_command") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN launch_command TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"launch_env") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN launch_env TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"aot_cache") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN aot_cache INTEGER NOT NULL DEFAULT 1").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"disable_sentry") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN disable_sentry INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"game_backup_enabled") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN game_backup_enabled INTEGER NOT NULL DEFAULT 0").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"game_backup_frequency") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN game_backup_frequency INTEGER").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"jvm_profile") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN jvm_profile TEXT").execute(pool).await.ok();
+    }
+    if !server_column_names.contains(&"java_runtime") {
+        sqlx::query("ALTER TABLE servers ADD COLUMN java_runtime TEXT").execute(pool).await.ok();
+    }
+
+    // Backups table migrations
+    let backup_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(backups)")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::other(e.to_string()))?;
+
+    let backup_column_names: Vec<&str> = backup_columns.iter().map(|c| c.1.as_str()).collect();
+
+    if !backup_column_names.contains(&"kind") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN kind TEXT NOT NULL DEFAULT 'archive'").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"checksum") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN checksum TEXT").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"verify_status") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN verify_status TEXT").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"verify_error") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN verify_error TEXT").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"verified_at") {
+        sqlx::query("ALTER TABLE backups ADD COLUMN verified_at TEXT").execute(pool).await.ok();
+    }
+    if !backup_column_names.contains(&"origin") {
+        // Existing backups are kept as manual ones so that retention never deletes them
+        sqlx::query("ALTER TABLE backups ADD COLUMN origin TEXT NOT NULL DEFAULT 'manual'").execute(pool).await.ok();
+        sqlx::query("UPDATE backups SET origin = 'pre_restore' WHERE substr(filename, 1, 12) = 'pre_restore_'").execute(pool).await.ok();
+    }
 
     // Server players table migrations
     let players_columns: Vec<(i64, String, String, i64, Option<String>, i64)> = sqlx::query_as("PRAGMA table_info(server_players)")
@@ -385,7 +559,7 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
         )
         .bind("user")
         .bind("Utilisateur")
-        .bind("[\"server.read\", \"server.console.read\"]") // Minimal set
+        .bind("[\"server.view\", \"server.console.read\"]") // Minimal set
         .bind(1)
         .bind(&now)
         .bind(&now)
@@ -393,6 +567,42 @@ pub async fn run_migrations(pool: &DbPool) -> std::io::Result<()> {
         .await.ok();
     }
 
+    // Launch profiles of the supported games; existing ones keep their edits
+    for (game_type, command, stop_command) in BUILTIN_PROFILES {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT OR IGNORE INTO launch_profiles (game_type, command, stop_command, created_at, updated_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(game_type)
+        .bind(command)
+        .bind(stop_command)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .ok();
+    }
+    // Built-in profiles left at a previous default get the new placeholders
+    let upgrades = [
+        ("hytale", HYTALE_COMMAND_V1, HYTALE_COMMAND),
+        ("minecraft", MINECRAFT_COMMAND_V1, MINECRAFT_COMMAND),
+    ];
+    for (game_type, previous, command) in upgrades {
+        sqlx::query("UPDATE launch_profiles SET command = ? WHERE game_type = ? AND command = ?")
+            .bind(command)
+            .bind(game_type)
+            .bind(previous)
+            .execute(pool)
+            .await
+            .ok();
+    }
+
+    // Roles migrations: "server.read" was renamed to "server.view"
+    sqlx::query("UPDATE roles SET permissions = REPLACE(permissions, '\"server.read\"', '\"server.view\"') WHERE permissions LIKE '%\"server.read\"%'")
+        .execute(pool)
+        .await
+        .ok();
+
     info!("✅ Migrations completed");
     Ok(())
 }
\ No newline at end of file
@@ -15,6 +15,7 @@ pub enum ErrorCode {
     AuthUserNotFound,
     AuthPasswordTooWeak,
     AuthRateLimited,
+    AuthPermissionDenied,
     
     // Server errors (SRV_xxx)
     ServerNotFound,
@@ -24,6 +25,11 @@ pub enum ErrorCode {
     ServerStopFailed,
     ServerDirMissing,
     ServerInstalling,
+    ServerAccessDenied,
+    ServerInvalidTransition,
+    ServerInvalidLaunchCommand,
+    ServerLaunchProfileNotFound,
+    ServerJavaUnsupported,
     
     // File system errors (FS_xxx)
     FileNotFound,
@@ -44,6 +50,22 @@ pub enum ErrorCode {
     BackupCreateFailed,
     BackupRestoreFailed,
     BackupDeleteFailed,
+    BackupInvalidMode,
+    BackupInvalidRule,
+    BackupPathNotFound,
+    BackupDestinationNotFound,
+    BackupInvalidDestination,
+    BackupReplicationFailed,
+    BackupCorrupted,
+    BackupSaveTimeout,
+    BackupInvalidArchive,
+    BackupSaveUnconfirmed,
+    
+    // Java runtime errors (RTM_xxx)
+    RuntimeNotFound,
+    RuntimeChecksumMismatch,
+    RuntimeInvalidArchive,
+    RuntimeInUse,
     
     // Validation errors (VAL_xxx)
     ValidationFailed,
@@ -68,6 +90,7 @@ impl ErrorCode {
             ErrorCode::AuthUserNotFound => "AUTH_006",
             ErrorCode::AuthPasswordTooWeak => "AUTH_007",
             ErrorCode::AuthRateLimited => "AUTH_008",
+            ErrorCode::AuthPermissionDenied => "AUTH_009",
             
             // Server
             ErrorCode::ServerNotFound => "SRV_001",
@@ -77,6 +100,11 @@ impl ErrorCode {
             ErrorCode::ServerStopFailed => "SRV_005",
             ErrorCode::ServerDirMissing => "SRV_006",
             ErrorCode::ServerInstalling => "SRV_007",
+            ErrorCode::ServerAccessDenied => "SRV_008",
+            ErrorCode::ServerInvalidTransition => "SRV_009",
+            ErrorCode::ServerInvalidLaunchCommand => "SRV_010",
+            ErrorCode::ServerLaunchProfileNotFound => "SRV_011",
+            ErrorCode::ServerJavaUnsupported => "SRV_012",
             
             // File system
             ErrorCode::FileNotFound => "FS_001",
@@ -97,6 +125,22 @@ impl ErrorCode {
             ErrorCode::BackupCreateFailed => "BKP_002",
             ErrorCode::BackupRestoreFailed => "BKP_003",
             ErrorCode::BackupDeleteFailed => "BKP_004",
+            ErrorCode::BackupInvalidMode => "BKP_005",
+            ErrorCode::BackupInvalidRule => "BKP_006",
+            ErrorCode::BackupPathNotFound => "BKP_007",
+            ErrorCode::BackupDestinationNotFound => "BKP_008",
+            ErrorCode::BackupInvalidDestination => "BKP_009",
+            ErrorCode::BackupReplicationFailed => "BKP_010",
+            ErrorCode::BackupCorrupted => "BKP_011",
+            ErrorCode::BackupSaveTimeout => "BKP_012",
+            ErrorCode::BackupInvalidArchive => "BKP_013",
+            ErrorCode::BackupSaveUnconfirmed => "BKP_014",
+            
+            // Java runtimes
+            ErrorCode::RuntimeNotFound => "RTM_001",
+            ErrorCode::RuntimeChecksumMismatch => "RTM_002",
+            ErrorCode::RuntimeInvalidArchive => "RTM_003",
+            ErrorCode::RuntimeInUse => "RTM_004",
             
             // Validation
             ErrorCode::ValidationFailed => "VAL_001",
@@ -1,6 +1,7 @@
 pub mod config;
 pub mod database;
 pub mod error;
+pub mod permissions;
 
 pub use config::Settings;
 pub use database::DbPool;
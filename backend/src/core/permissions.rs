@@ -0,0 +1,104 @@
+// Named permissions checked by API handlers
+// Roles store these names as a JSON array; "*" and "prefix.*" act as wildcards
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    // Servers
+    ServerView,
+    ServerCreate,
+    ServerEdit,
+    ServerDelete,
+    ServerStart,
+    ServerStop,
+    ServerRestart,
+    ServerKill,
+    ServerReinstall,
+
+    // Console
+    ServerConsoleRead,
+    ServerConsoleWrite,
+
+    // Files
+    ServerFilesRead,
+    ServerFilesWrite,
+    ServerFilesDelete,
+
+    // Players
+    ServerPlayersManage,
+
+    // Backups
+    ServerBackupsView,
+    ServerBackupsCreate,
+    ServerBackupsRestore,
+    ServerBackupsDelete,
+
+    // Schedules
+    ServerSchedulesManage,
+
+    // Administration
+    UsersManage,
+    RolesManage,
+    SettingsManage,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ServerView => "server.view",
+            Permission::ServerCreate => "server.create",
+            Permission::ServerEdit => "server.edit",
+            Permission::ServerDelete => "server.delete",
+            Permission::ServerStart => "server.start",
+            Permission::ServerStop => "server.stop",
+            Permission::ServerRestart => "server.restart",
+            Permission::ServerKill => "server.kill",
+            Permission::ServerReinstall => "server.reinstall",
+
+            Permission::ServerConsoleRead => "server.console.read",
+            Permission::ServerConsoleWrite => "server.console.write",
+
+            Permission::ServerFilesRead => "server.files.read",
+            Permission::ServerFilesWrite => "server.files.write",
+            Permission::ServerFilesDelete => "server.files.delete",
+
+            Permission::ServerPlayersManage => "server.players.manage",
+
+            Permission::ServerBackupsView => "server.backups.view",
+            Permission::ServerBackupsCreate => "server.backups.create",
+            Permission::ServerBackupsRestore => "server.backups.restore",
+            Permission::ServerBackupsDelete => "server.backups.delete",
+
+            Permission::ServerSchedulesManage => "server.schedules.manage",
+
+            Permission::UsersManage => "users.manage",
+            Permission::RolesManage => "roles.manage",
+            Permission::SettingsManage => "settings.manage",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Check whether a list of granted permission names covers `required`.
+/// Supports the global wildcard "*" and prefix wildcards like "server.*".
+pub fn is_granted(granted: &[String], required: Permission) -> bool {
+    let required = required.as_str();
+
+    granted.iter().any(|perm| {
+        if perm == "*" || perm == required {
+            return true;
+        }
+        match perm.strip_suffix(".*") {
+            Some(prefix) => required
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('.')),
+            None => false,
+        }
+    })
+}
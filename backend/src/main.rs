@@ -53,12 +53,15 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database
     let pool = database::init_pool(&settings.database_url).await?;
     database::run_migrations(&pool).await?;
+    services::system::backup::init_backups_dir(&pool).await?;
 
     // Initialize services
     let process_manager = ProcessManager::new(Some(pool.clone()));
+    process_manager.reattach().await;
 
     // Start background services
     services::system::scheduler::start(pool.clone(), process_manager.clone());
+    services::system::autostart::start(pool.clone(), process_manager.clone());
 
     let state = AppState {
         pool,
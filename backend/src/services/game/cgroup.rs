@@ -0,0 +1,195 @@
+// cgroup v2 resource limits
+// On Linux each native server runs in its own cgroup below the panel's, with memory, CPU, PID and
+// IO limits from its settings. The panel moves itself into a `panel` leaf so that its cgroup can
+// hand the controllers down to the server cgroups (under systemd this needs `Delegate=yes`).
+// Servers in a cgroup of their own, containers included, are measured from the cgroup accounting.
+
+use std::path::PathBuf;
+
+/// Limits of a server; unset values are unlimited
+#[derive(Debug, Clone, Default)]
+pub struct ResourceLimits {
+    pub memory_bytes: Option<u64>,
+    /// Number of CPUs, e.g. 1.5
+    pub cpus: Option<f64>,
+    pub pids: Option<u32>,
+    /// Relative IO weight, from 1 to 10000 (100 by default)
+    pub io_weight: Option<u16>,
+}
+
+pub struct Usage {
+    /// Total CPU time used, in microseconds
+    pub cpu_usec: u64,
+    pub memory_bytes: u64,
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    use tracing::warn;
+
+    use super::{ResourceLimits, Usage};
+
+    const CGROUP_MOUNT: &str = "/sys/fs/cgroup";
+    const CONTROLLERS: [&str; 4] = ["cpu", "memory", "pids", "io"];
+    const PANEL_LEAF: &str = "panel";
+    /// Period of the CPU bandwidth limit, in microseconds
+    const CPU_PERIOD_USEC: u64 = 100_000;
+
+    static SERVERS_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    /// Cgroup the server cgroups are created in; `None` when the panel cannot delegate controllers
+    fn servers_root() -> Option<&'static Path> {
+        SERVERS_ROOT
+            .get_or_init(|| match init_root() {
+                Ok(root) => Some(root),
+                Err(e) => {
+                    warn!("cgroup v2 limits are unavailable, servers run unconstrained: {}", e);
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    fn init_root() -> io::Result<PathBuf> {
+        let mount = Path::new(CGROUP_MOUNT);
+        if !mount.join("cgroup.controllers").exists() {
+            return Err(io::Error::other("cgroup v2 is not mounted"));
+        }
+        let own = process_cgroup(std::process::id())
+            .ok_or_else(|| io::Error::other("cannot read the panel's cgroup"))?;
+
+        let root = match own.strip_suffix(&format!("/{PANEL_LEAF}")) {
+            // Already moved by a previous start of this process tree
+            Some(parent) => mount.join(parent.trim_start_matches('/')),
+            None => mount.join(own.trim_start_matches('/')),
+        };
+        // Only leaf cgroups may hold processes once controllers are handed down; the root is exempt
+        if root != mount {
+            let leaf = root.join(PANEL_LEAF);
+            fs::create_dir_all(&leaf)?;
+            fs::write(leaf.join("cgroup.procs"), std::process::id().to_string())?;
+        }
+
+        let available = fs::read_to_string(root.join("cgroup.controllers"))?;
+        for controller in CONTROLLERS {
+            if available.split_whitespace().any(|c| c == controller) {
+                fs::write(root.join("cgroup.subtree_control"), format!("+{controller}"))?;
+            }
+        }
+        Ok(root)
+    }
+
+    /// cgroup v2 path of a process, relative to the mount point
+    fn process_cgroup(pid: u32) -> Option<String> {
+        let content = fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+        content.lines().find_map(|line| line.strip_prefix("0::")).map(str::to_string)
+    }
+
+    pub fn prepare(server_id: &str, limits: &ResourceLimits) -> Option<PathBuf> {
+        let path = servers_root()?.join(format!("server-{server_id}"));
+        if let Err(e) = fs::create_dir_all(&path).and_then(|_| apply(&path, limits)) {
+            warn!("Cannot set up the cgroup of server {}: {}", server_id, e);
+            return None;
+        }
+        Some(path)
+    }
+
+    pub fn remove(server_id: &str) -> io::Result<()> {
+        let Some(root) = servers_root() else { return Ok(()) };
+        match fs::remove_dir(root.join(format!("server-{server_id}"))) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(path: &Path, limits: &ResourceLimits) -> io::Result<()> {
+        let memory = limits.memory_bytes.map_or("max".to_string(), |b| b.to_string());
+        let cpu = match limits.cpus {
+            Some(cpus) => format!("{} {CPU_PERIOD_USEC}", ((cpus * CPU_PERIOD_USEC as f64) as u64).max(1000)),
+            None => format!("max {CPU_PERIOD_USEC}"),
+        };
+        let settings = [
+            ("memory.max", memory),
+            // No swap on top of the memory limit
+            ("memory.swap.max", if limits.memory_bytes.is_some() { "0".to_string() } else { "max".to_string() }),
+            ("cpu.max", cpu),
+            ("pids.max", limits.pids.map_or("max".to_string(), |p| p.to_string())),
+            ("io.weight", format!("default {}", limits.io_weight.unwrap_or(100))),
+        ];
+        // Files of controllers the system does not provide are missing
+        for (file, value) in settings {
+            if path.join(file).exists() {
+                fs::write(path.join(file), value)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn of_process(pid: u32) -> Option<PathBuf> {
+        let cgroup = process_cgroup(pid)?;
+        let own = process_cgroup(std::process::id());
+        if cgroup == "/" || Some(&cgroup) == own.as_ref() {
+            return None;
+        }
+        let path = Path::new(CGROUP_MOUNT).join(cgroup.trim_start_matches('/'));
+        path.join("memory.current").exists().then_some(path)
+    }
+
+    pub fn usage(cgroup: &Path) -> Option<Usage> {
+        let memory_bytes = fs::read_to_string(cgroup.join("memory.current")).ok()?.trim().parse().ok()?;
+        let cpu_stat = fs::read_to_string(cgroup.join("cpu.stat")).ok()?;
+        let cpu_usec = cpu_stat.lines()
+            .find_map(|line| line.strip_prefix("usage_usec "))
+            .and_then(|v| v.trim().parse().ok())?;
+        Some(Usage { cpu_usec, memory_bytes })
+    }
+}
+
+/// Create (or reuse) the cgroup of a server with `limits` applied. `None` when limits are unavailable.
+pub fn prepare(server_id: &str, limits: &ResourceLimits) -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    return linux::prepare(server_id, limits);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (server_id, limits);
+        None
+    }
+}
+
+/// Remove the cgroup of a server, if it has one; fails while processes are still in it
+pub fn remove(server_id: &str) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    return linux::remove(server_id);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = server_id;
+        Ok(())
+    }
+}
+
+/// cgroup of a process, if it has one of its own (not the panel's)
+pub fn of_process(pid: u32) -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    return linux::of_process(pid);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+/// Current accounting of a cgroup
+pub fn usage(cgroup: &std::path::Path) -> Option<Usage> {
+    #[cfg(target_os = "linux")]
+    return linux::usage(cgroup);
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = cgroup;
+        None
+    }
+}
@@ -0,0 +1,336 @@
+// Container execution backend
+// A server can run in a Docker container instead of a host process: the image provides Java, the
+// working directory is bind-mounted on /data and the runtime enforces the resource limits.
+// The console goes through the Docker API (attach); the output and error streams are appended to the
+// same console logs as for native servers, so the manager follows both the same way.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use bollard::container::{
+    AttachContainerOptions, Config, CreateContainerOptions, KillContainerOptions, LogOutput,
+    RemoveContainerOptions, StartContainerOptions, WaitContainerOptions,
+};
+use bollard::image::CreateImageOptions;
+use bollard::models::{ContainerState, HostConfig, PortBinding};
+use bollard::Docker;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::cgroup::ResourceLimits;
+use super::logs;
+use super::process::{console_log_path, stderr_log_path, Exit};
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+pub const BACKEND_NATIVE: &str = "native";
+pub const BACKEND_DOCKER: &str = "docker";
+/// Used when a containerized server has no image of its own
+pub const DEFAULT_IMAGE: &str = "eclipse-temurin:25-jre";
+/// Where the working directory is mounted inside the container
+pub const DATA_DIR: &str = "/data";
+
+/// How a containerized server is run, from its settings
+#[derive(Debug, Clone)]
+pub struct ContainerSettings {
+    pub image: String,
+}
+
+/// Everything needed to create the container of a server
+pub struct ContainerSpec<'a> {
+    pub settings: &'a ContainerSettings,
+    pub working_dir: &'a Path,
+    /// Command line, the program first
+    pub command: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub limits: &'a ResourceLimits,
+    /// Memory limit when `limits` has none
+    pub memory_bytes: u64,
+    pub bind_address: &'a str,
+    pub port: u16,
+}
+
+type Output = Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>;
+
+pub struct ContainerProcess {
+    docker: Docker,
+    name: String,
+    pid: u32,
+    started_at: Option<DateTime<Utc>>,
+    /// Locked while a line is written
+    input: tokio::sync::Mutex<Pin<Box<dyn AsyncWrite + Send>>>,
+    /// Set once the container has exited and its output has been written out
+    exit: Arc<Mutex<Option<Exit>>>,
+}
+
+pub fn container_name(server_id: &str) -> String {
+    format!("draveur-{server_id}")
+}
+
+/// Path of `path` as seen from inside the container: paths under the working directory are
+/// remapped to the mount point, the others are left as they are
+pub fn container_path(working_dir: &Path, path: &str) -> String {
+    match Path::new(path).strip_prefix(working_dir) {
+        Ok(relative) => format!("{DATA_DIR}/{}", relative.to_string_lossy().replace('\\', "/")),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn connect() -> Result<Docker, AppError> {
+    Docker::connect_with_local_defaults()
+        .map_err(|e| AppError::Internal(format!("Cannot connect to Docker: {e}")).with_code(ErrorCode::ServerStartFailed))
+}
+
+fn docker_error(context: &str, e: bollard::errors::Error) -> AppError {
+    AppError::Internal(format!("{context}: {e}")).with_code(ErrorCode::ServerStartFailed)
+}
+
+impl ContainerProcess {
+    /// Create and start the container of `server_id`, replacing any container left from a previous run.
+    /// Its output goes to new console logs.
+    pub async fn start(server_id: &str, spec: ContainerSpec<'_>) -> Result<Self, AppError> {
+        let docker = connect()?;
+        let name = container_name(server_id);
+
+        let _ = docker
+            .remove_container(&name, Some(RemoveContainerOptions { force: true, ..Default::default() }))
+            .await;
+        ensure_image(&docker, &spec.settings.image).await?;
+
+        logs::rotate_console(spec.working_dir)?;
+
+        let mut port_bindings = HashMap::new();
+        let mut exposed_ports = HashMap::new();
+        for protocol in ["tcp", "udp"] {
+            let port = format!("{}/{protocol}", spec.port);
+            port_bindings.insert(port.clone(), Some(vec![PortBinding {
+                host_ip: Some(spec.bind_address.to_string()),
+                host_port: Some(spec.port.to_string()),
+            }]));
+            exposed_ports.insert(port, HashMap::new());
+        }
+
+        let working_dir = std::fs::canonicalize(spec.working_dir)?;
+        let memory = i64::try_from(spec.limits.memory_bytes.unwrap_or(spec.memory_bytes)).unwrap_or(i64::MAX);
+        let host_config = HostConfig {
+            binds: Some(vec![format!("{}:{DATA_DIR}", working_dir.display())]),
+            memory: Some(memory),
+            // No swap on top of the memory limit
+            memory_swap: Some(memory),
+            nano_cpus: spec.limits.cpus.map(|cpus| (cpus * 1e9) as i64),
+            pids_limit: spec.limits.pids.map(i64::from),
+            // Docker only accepts weights from 10 to 1000
+            blkio_weight: spec.limits.io_weight.map(|w| w.clamp(10, 1000)),
+            port_bindings: Some(port_bindings),
+            ..Default::default()
+        };
+
+        let config = Config {
+            image: Some(spec.settings.image.clone()),
+            cmd: Some(spec.command),
+            env: Some(spec.env.iter().map(|(key, value)| format!("{key}={value}")).collect()),
+            working_dir: Some(DATA_DIR.to_string()),
+            // Files written to the mount keep the panel's ownership
+            user: panel_user(),
+            labels: Some(HashMap::from([("draveur.server".to_string(), server_id.to_string())])),
+            exposed_ports: Some(exposed_ports),
+            open_stdin: Some(true),
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            tty: Some(false),
+            host_config: Some(host_config),
+            ..Default::default()
+        };
+
+        docker
+            .create_container(Some(CreateContainerOptions { name: name.clone(), platform: None }), config)
+            .await
+            .map_err(|e| docker_error("Failed to create the container", e))?;
+
+        // Attached before the start so that no output is missed
+        let attached = attach_streams(&docker, &name).await;
+        let started = match attached {
+            Ok(streams) => docker
+                .start_container(&name, None::<StartContainerOptions<String>>)
+                .await
+                .map(|_| streams),
+            Err(e) => Err(e),
+        };
+        let (output, input) = match started {
+            Ok(streams) => streams,
+            Err(e) => {
+                let _ = docker
+                    .remove_container(&name, Some(RemoveContainerOptions { force: true, ..Default::default() }))
+                    .await;
+                return Err(docker_error("Failed to start the container", e));
+            }
+        };
+
+        let state = running_state(&docker, &name).await.unwrap_or_default();
+        Ok(Self::follow(docker, name, &state, output, input, spec.working_dir))
+    }
+
+    /// Find the running container of `server_id` left by a previous panel instance
+    pub async fn attach(server_id: &str, working_dir: &Path) -> Option<Self> {
+        let docker = Docker::connect_with_local_defaults().ok()?;
+        let name = container_name(server_id);
+        let state = running_state(&docker, &name).await?;
+        let (output, input) = attach_streams(&docker, &name).await.ok()?;
+        Some(Self::follow(docker, name, &state, output, input, working_dir))
+    }
+
+    /// Append the container output and errors to the console logs until it exits, then record the exit code
+    fn follow(docker: Docker, name: String, state: &ContainerState, mut output: Output, input: Pin<Box<dyn AsyncWrite + Send>>, working_dir: &Path) -> Self {
+        let exit = Arc::new(Mutex::new(None));
+
+        let exit_cell = exit.clone();
+        let waiter = docker.clone();
+        let container = name.clone();
+        let (stdout_path, stderr_path) = (console_log_path(working_dir), stderr_log_path(working_dir));
+        tokio::spawn(async move {
+            let mut stdout = LogWriter::open(stdout_path).await;
+            let mut stderr = LogWriter::open(stderr_path).await;
+            while let Some(Ok(chunk)) = output.next().await {
+                match chunk {
+                    LogOutput::StdOut { message } => stdout.write(&message).await,
+                    LogOutput::StdErr { message } => stderr.write(&message).await,
+                    _ => {}
+                }
+            }
+            stdout.write(b"[Server Stopped]\n").await;
+            stdout.flush().await;
+            stderr.flush().await;
+
+            let code = match waiter.wait_container(&container, None::<WaitContainerOptions<String>>).next().await {
+                Some(Ok(response)) => i32::try_from(response.status_code).ok(),
+                Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => i32::try_from(code).ok(),
+                _ => None,
+            };
+            *exit_cell.lock().unwrap_or_else(|e| e.into_inner()) = Some(Exit { code });
+        });
+
+        Self {
+            docker,
+            name,
+            pid: state.pid.and_then(|pid| u32::try_from(pid).ok()).unwrap_or_default(),
+            started_at: state.started_at.as_deref()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|t| t.with_timezone(&Utc)),
+            input: tokio::sync::Mutex::new(input),
+            exit,
+        }
+    }
+
+    /// Host PID of the server process
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.started_at
+    }
+
+    pub fn try_exit(&self) -> Option<Exit> {
+        *self.exit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub async fn send_line(&self, line: &str) -> std::io::Result<()> {
+        let mut input = self.input.lock().await;
+        input.write_all(format!("{line}\n").as_bytes()).await?;
+        input.flush().await
+    }
+
+    pub async fn kill(&self) -> std::io::Result<()> {
+        match self.docker.kill_container(&self.name, Some(KillContainerOptions { signal: "SIGKILL" })).await {
+            Ok(()) => Ok(()),
+            // Already gone or no longer running
+            Err(bollard::errors::Error::DockerResponseServerError { status_code: 404 | 409, .. }) => Ok(()),
+            Err(e) => Err(std::io::Error::other(e)),
+        }
+    }
+}
+
+/// A console log written by the panel, which rotates it once too large
+struct LogWriter {
+    path: PathBuf,
+    file: Option<tokio::fs::File>,
+    written: u64,
+}
+
+impl LogWriter {
+    async fn open(path: PathBuf) -> Self {
+        let file = tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await.ok();
+        let written = match file.as_ref() {
+            Some(file) => file.metadata().await.map(|m| m.len()).unwrap_or(0),
+            None => 0,
+        };
+        Self { path, file, written }
+    }
+
+    async fn write(&mut self, message: &[u8]) {
+        let Some(file) = self.file.as_mut() else { return };
+        let _ = file.write_all(message).await;
+        self.written += message.len() as u64;
+
+        // Rotated here by its writer, between two lines, so that none is lost or split
+        if self.written > logs::MAX_LOG_BYTES && message.ends_with(b"\n") {
+            let _ = file.flush().await;
+            match logs::rotate(&self.path) {
+                Ok(()) => *self = Self::open(self.path.clone()).await,
+                Err(e) => tracing::warn!("Failed to rotate {}: {}", self.path.display(), e),
+            }
+        }
+    }
+
+    async fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush().await;
+        }
+    }
+}
+
+async fn ensure_image(docker: &Docker, image: &str) -> Result<(), AppError> {
+    if docker.inspect_image(image).await.is_ok() {
+        return Ok(());
+    }
+    tracing::info!("Pulling image {}", image);
+    let mut pull = docker.create_image(Some(CreateImageOptions { from_image: image, ..Default::default() }), None, None);
+    while let Some(progress) = pull.next().await {
+        progress.map_err(|e| docker_error(&format!("Failed to pull image {image}"), e))?;
+    }
+    Ok(())
+}
+
+async fn attach_streams(docker: &Docker, name: &str) -> Result<(Output, Pin<Box<dyn AsyncWrite + Send>>), bollard::errors::Error> {
+    let options = AttachContainerOptions::<String> {
+        stdin: Some(true),
+        stdout: Some(true),
+        stderr: Some(true),
+        stream: Some(true),
+        ..Default::default()
+    };
+    let attached = docker.attach_container(name, Some(options)).await?;
+    Ok((attached.output, attached.input))
+}
+
+/// State of a container, if it is running
+async fn running_state(docker: &Docker, name: &str) -> Option<ContainerState> {
+    let state = docker.inspect_container(name, None).await.ok()?.state?;
+    (state.running == Some(true)).then_some(state)
+}
+
+#[cfg(unix)]
+fn panel_user() -> Option<String> {
+    // SAFETY: these calls cannot fail and touch no memory
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    Some(format!("{uid}:{gid}"))
+}
+
+#[cfg(not(unix))]
+fn panel_user() -> Option<String> {
+    None
+}
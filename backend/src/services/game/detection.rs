@@ -6,6 +6,43 @@ pub struct PlayerDetectionPatterns {
     pub leave_regex: Regex,
     pub server_ready_regex: Regex,
     pub ip_regex: Option<Regex>,
+    /// Printed once the world has been written to disk after a save command. Running servers of
+    /// games without a known confirmation can't be backed up.
+    pub save_complete_regex: Option<Regex>,
+}
+
+/// Game-specific console commands the panel sends on its own
+pub struct GameCommands {
+    /// Flush the world to disk
+    pub save: &'static str,
+    pub disable_autosave: Option<&'static str>,
+    pub enable_autosave: Option<&'static str>,
+    /// Default stop command, unless the server has its own
+    pub stop: &'static str,
+    /// Prefix of a message shown to every player
+    pub say: &'static str,
+}
+
+impl GameCommands {
+    pub fn for_game_type(game_type: &str) -> Self {
+        match game_type.to_lowercase().as_str() {
+            "minecraft" => Self {
+                save: "save-all flush",
+                disable_autosave: Some("save-off"),
+                enable_autosave: Some("save-on"),
+                stop: "stop",
+                say: "say",
+            },
+            // Hytale has no command to pause saving: the world is only flushed before the backup
+            _ => Self {
+                save: "/save-all",
+                disable_autosave: None,
+                enable_autosave: None,
+                stop: "/shutdown",
+                say: "/say",
+            },
+        }
+    }
 }
 
 impl PlayerDetectionPatterns {
@@ -22,6 +59,7 @@ impl PlayerDetectionPatterns {
     /// Join: "[Universe|P] Adding player 'TheFRcRaZy (uuid)'"
     /// Leave: "[Universe|P] Removing player 'TheFRcRaZy' (uuid)"
     /// Ready: "[HytaleServer] Universe ready!"
+    /// Saved: not confirmed by a known log line
     fn hytale() -> Self {
         Self {
             // Join: [Universe|P] Adding player 'TheFRcRaZy' (uuid)
@@ -32,6 +70,7 @@ impl PlayerDetectionPatterns {
             server_ready_regex: Regex::new(r"Universe ready!").unwrap(),
             // IP: {Playing(QuicConnectionAddress{...} (/82.64.248.19:55745, ...)), UUID, Name}
             ip_regex: Some(Regex::new(r"\{Playing\(.+? \(/([\d\.]+):\d+.*?\)\), ([0-9a-f-]+), ([^}]+)\}").unwrap()),
+            save_complete_regex: None,
         }
     }
 
@@ -39,12 +78,14 @@ impl PlayerDetectionPatterns {
     /// Join: "[Server thread/INFO]: PlayerName joined the game"
     /// Leave: "[Server thread/INFO]: PlayerName left the game"
     /// Ready: "Done (X.XXXs)! For help, type "help""
+    /// Saved: "[Server thread/INFO]: Saved the game" ("Save complete." before 1.13)
     fn minecraft() -> Self {
         Self {
             join_regex: Regex::new(r"\[.*\]: (.*) joined the game").unwrap(),
             leave_regex: Regex::new(r"\[.*\]: (.*) left the game").unwrap(),
             server_ready_regex: Regex::new(r"Done \([\d.]+s\)! For help").unwrap(),
             ip_regex: None,
+            save_complete_regex: Some(Regex::new(r"\]: (Saved the game|Save complete\.)").unwrap()),
         }
     }
 }
@@ -0,0 +1,92 @@
+// Console events
+// What a server's console broadcasts to its clients. The console WebSocket renders each event
+// either as a line of the legacy text protocol (`[STATUS]: running`, `[METRICS]: {...}`) or as a
+// message of the versioned JSON protocol (see `api::console`).
+// Log lines tell which stream they were written to: game servers write their standard output and
+// error to separate console logs, the panel's own commands (installation) are read from pipes.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerAction {
+    Join,
+    Leave,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConsoleEvent {
+    Log {
+        line: String,
+        stream: Stream,
+        timestamp: DateTime<Utc>,
+    },
+    /// A lifecycle state ("running", "stopped", ...) or a milestone ("booted", "auth_success")
+    Status(String),
+    Metrics(serde_json::Value),
+    Player {
+        action: PlayerAction,
+        name: String,
+    },
+    /// Message from the panel itself, e.g. the watchdog
+    Notice {
+        source: &'static str,
+        message: String,
+    },
+}
+
+lazy_static::lazy_static! {
+    // "[12:00:00] [Server thread/INFO]:", "[2026/01/01 12:00:00   WARN]", "[12:00:00 ERROR]:"
+    static ref LEVEL_REGEX: Regex = Regex::new(
+        r"^(?:\[[^\]]*\]\s*)*?\[[^\]]*?\b(TRACE|DEBUG|FINE|FINER|FINEST|INFO|WARN|WARNING|ERROR|SEVERE|FATAL)\]"
+    ).expect("valid level regex");
+}
+
+impl ConsoleEvent {
+    pub fn log(line: String, stream: Stream) -> Self {
+        ConsoleEvent::Log { line, stream, timestamp: Utc::now() }
+    }
+
+    /// Line of the legacy text protocol, which does not tell the streams apart; player events
+    /// have none
+    pub fn to_text(&self) -> Option<String> {
+        Some(match self {
+            ConsoleEvent::Log { line, .. } => line.clone(),
+            ConsoleEvent::Status(status) => format!("[STATUS]: {status}"),
+            ConsoleEvent::Metrics(metrics) => format!("[METRICS]: {metrics}"),
+            ConsoleEvent::Notice { source, message } => format!("[{}]: {message}", source.to_uppercase()),
+            ConsoleEvent::Player { .. } => return None,
+        })
+    }
+}
+
+/// Level of a log line, from the tag games put in the line's leading brackets
+pub fn parse_level(line: &str) -> Option<Level> {
+    let level = LEVEL_REGEX.captures(line)?.get(1)?.as_str();
+    Some(match level {
+        "TRACE" | "FINER" | "FINEST" => Level::Trace,
+        "DEBUG" | "FINE" => Level::Debug,
+        "INFO" => Level::Info,
+        "WARN" | "WARNING" => Level::Warn,
+        _ => Level::Error,
+    })
+}
@@ -0,0 +1,101 @@
+// JVM tuning profiles
+// Named presets a server can pick instead of hand-written GC flags. A profile sets the heap sizing
+// and the flags substituted for `{jvm_flags}` in the launch command; servers without one keep the
+// default sizing and no flags. Each profile needs a minimum Java version, checked when it is selected.
+
+use serde::Serialize;
+
+use crate::utils::memory::calculate_jvm_tokens;
+
+#[derive(Debug, Serialize)]
+pub struct JvmProfile {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Lowest Java feature release the flags work with
+    pub min_java: u32,
+    /// -Xms equal to -Xmx: the whole heap is reserved at start
+    pub fixed_heap: bool,
+    flags: &'static [&'static str],
+    /// Replace `flags` from 12 GB of heap, where larger regions and young generation pay off
+    large_heap_flags: Option<&'static [&'static str]>,
+}
+
+const LARGE_HEAP_BYTES: u64 = 12 * 1024 * 1024 * 1024;
+
+const AIKAR_FLAGS: [&str; 17] = [
+    "-XX:+UseG1GC", "-XX:+ParallelRefProcEnabled", "-XX:MaxGCPauseMillis=200",
+    "-XX:+UnlockExperimentalVMOptions", "-XX:+DisableExplicitGC", "-XX:+AlwaysPreTouch",
+    "-XX:G1NewSizePercent=30", "-XX:G1MaxNewSizePercent=40", "-XX:G1HeapRegionSize=8M",
+    "-XX:G1ReservePercent=20", "-XX:G1HeapWastePercent=5", "-XX:G1MixedGCCountTarget=4",
+    "-XX:InitiatingHeapOccupancyPercent=15", "-XX:G1MixedGCLiveThresholdPercent=90",
+    "-XX:SurvivorRatio=32", "-XX:+PerfDisableSharedMem", "-XX:MaxTenuringThreshold=1",
+];
+
+const AIKAR_LARGE_HEAP_FLAGS: [&str; 17] = [
+    "-XX:+UseG1GC", "-XX:+ParallelRefProcEnabled", "-XX:MaxGCPauseMillis=200",
+    "-XX:+UnlockExperimentalVMOptions", "-XX:+DisableExplicitGC", "-XX:+AlwaysPreTouch",
+    "-XX:G1NewSizePercent=40", "-XX:G1MaxNewSizePercent=50", "-XX:G1HeapRegionSize=16M",
+    "-XX:G1ReservePercent=15", "-XX:G1HeapWastePercent=5", "-XX:G1MixedGCCountTarget=4",
+    "-XX:InitiatingHeapOccupancyPercent=20", "-XX:G1MixedGCLiveThresholdPercent=90",
+    "-XX:SurvivorRatio=32", "-XX:+PerfDisableSharedMem", "-XX:MaxTenuringThreshold=1",
+];
+
+pub const PROFILES: [JvmProfile; 3] = [
+    JvmProfile {
+        id: "g1",
+        name: "G1 balanced",
+        min_java: 11,
+        fixed_heap: false,
+        flags: &[
+            "-XX:+UseG1GC", "-XX:MaxGCPauseMillis=200", "-XX:+ParallelRefProcEnabled",
+            "-XX:+UseStringDeduplication", "-XX:+DisableExplicitGC",
+        ],
+        large_heap_flags: None,
+    },
+    JvmProfile {
+        id: "zgc",
+        name: "ZGC low latency",
+        // ZGC is generational by default from Java 23
+        min_java: 23,
+        fixed_heap: true,
+        flags: &["-XX:+UseZGC", "-XX:+AlwaysPreTouch", "-XX:+DisableExplicitGC"],
+        large_heap_flags: None,
+    },
+    JvmProfile {
+        id: "aikar",
+        name: "Aikar's flags",
+        min_java: 11,
+        fixed_heap: true,
+        flags: &AIKAR_FLAGS,
+        large_heap_flags: Some(&AIKAR_LARGE_HEAP_FLAGS),
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static JvmProfile> {
+    PROFILES.iter().find(|p| p.id == id)
+}
+
+impl JvmProfile {
+    /// -Xms and -Xmx for a heap of `heap_bytes`
+    pub fn heap(&self, heap_bytes: u64) -> (String, String) {
+        let (xms, xmx) = calculate_jvm_tokens(heap_bytes);
+        if self.fixed_heap { (xmx.clone(), xmx) } else { (xms, xmx) }
+    }
+
+    pub fn flags(&self, heap_bytes: u64) -> Vec<String> {
+        let flags = match self.large_heap_flags {
+            Some(large) if heap_bytes >= LARGE_HEAP_BYTES => large,
+            _ => self.flags,
+        };
+        flags.iter().map(|f| f.to_string()).collect()
+    }
+}
+
+/// Feature release of a `java -version` string: "1.8.0_392" is 8, "21.0.2" is 21
+pub fn major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
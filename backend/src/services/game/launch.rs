@@ -0,0 +1,210 @@
+// Launch profiles
+// A server's command line comes from a template: the launch profile of its game type, stored in the
+// database so that new games can be added from the API, unless the server has a template of its own.
+// Templates are split on whitespace before the variables are substituted, so a value containing
+// spaces (a path) stays one argument; list variables (`{extra_args}`, `{jvm_flags}`, `{game_flags}`)
+// written as a whole word expand to one argument per item. A server's extra arguments are split
+// with shell quoting rules first.
+
+use std::collections::HashMap;
+
+use sqlx::FromRow;
+
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+pub const VARIABLES: [&str; 9] = ["java", "xms", "xmx", "port", "bind", "jar", "extra_args", "jvm_flags", "game_flags"];
+
+pub const HYTALE_COMMAND: &str =
+    "{java} -Xms{xms} -Xmx{xmx} {jvm_flags} -Dterminal.jline=true -Dterminal.ansi=true {extra_args} -jar {jar} --assets Assets.zip --bind {bind}:{port} {game_flags}";
+/// Default Hytale command before the typed options, upgraded in place when unchanged
+pub const HYTALE_COMMAND_V1: &str =
+    "{java} -Xms{xms} -Xmx{xmx} -Dterminal.jline=true -Dterminal.ansi=true {extra_args} -jar {jar} --assets Assets.zip --bind {bind}:{port}";
+/// Shipped next to the server jar
+pub const HYTALE_AOT_CACHE: &str = "HytaleServer.aot";
+pub const AUTH_MODES: [&str; 2] = ["authenticated", "offline"];
+pub const MINECRAFT_COMMAND: &str = "{java} -Xms{xms} -Xmx{xmx} {jvm_flags} {extra_args} -jar {jar} --port {port} nogui";
+/// Default Minecraft command before the JVM profiles, upgraded in place when unchanged
+pub const MINECRAFT_COMMAND_V1: &str = "{java} -Xms{xms} -Xmx{xmx} {extra_args} -jar {jar} --port {port} nogui";
+
+/// Profiles created with the database as (game type, command, stop command); they can be edited like the others
+pub const BUILTIN_PROFILES: [(&str, &str, &str); 2] = [
+    ("hytale", HYTALE_COMMAND, "/shutdown"),
+    ("minecraft", MINECRAFT_COMMAND, "stop"),
+];
+
+#[derive(Debug, Clone, FromRow)]
+pub struct LaunchProfile {
+    pub game_type: String,
+    pub command: String,
+    /// JSON object of environment variables, whose values are templates too
+    pub env: Option<String>,
+    pub stop_command: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl LaunchProfile {
+    pub fn env(&self) -> HashMap<String, String> {
+        env_from_column(self.env.as_deref())
+    }
+}
+
+pub fn env_from_column(column: Option<&str>) -> HashMap<String, String> {
+    column
+        .and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or_default()
+}
+
+pub async fn load_profile(pool: &DbPool, game_type: &str) -> Result<Option<LaunchProfile>, AppError> {
+    Ok(sqlx::query_as("SELECT * FROM launch_profiles WHERE game_type = ?")
+        .bind(game_type.to_lowercase())
+        .fetch_optional(pool)
+        .await?)
+}
+
+/// Typed launch options of a Hytale server
+#[derive(Debug, Clone)]
+pub struct HytaleOptions {
+    pub auth_mode: String,
+    /// Use the AOT cache shipped next to the server jar, when there is one
+    pub aot_cache: bool,
+    pub disable_sentry: bool,
+    /// The server's own world backups
+    pub backup: bool,
+    /// Minutes between two of them
+    pub backup_frequency: Option<u32>,
+}
+
+impl HytaleOptions {
+    /// JVM flags and server flags. `aot_file` is the AOT cache as the JVM sees it, when it exists.
+    pub fn flags(&self, aot_file: Option<String>) -> (Vec<String>, Vec<String>) {
+        let mut jvm = Vec::new();
+        if let Some(aot_file) = aot_file.filter(|_| self.aot_cache) {
+            jvm.push(format!("-XX:AOTCache={aot_file}"));
+        }
+
+        let mut game = vec!["--auth-mode".to_string(), self.auth_mode.clone()];
+        if self.disable_sentry {
+            game.push("--disable-sentry".to_string());
+        }
+        if self.backup {
+            game.push("--backup".to_string());
+            if let Some(minutes) = self.backup_frequency {
+                game.extend(["--backup-frequency".to_string(), minutes.to_string()]);
+            }
+        }
+        (jvm, game)
+    }
+}
+
+/// Values of the template variables for one start
+pub struct LaunchVars<'a> {
+    pub java: &'a str,
+    pub xms: String,
+    pub xmx: String,
+    pub port: u16,
+    pub bind: &'a str,
+    pub jar: &'a str,
+    pub extra_args: Vec<String>,
+    pub jvm_flags: Vec<String>,
+    pub game_flags: Vec<String>,
+}
+
+impl LaunchVars<'_> {
+    fn value(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "java" => self.java.to_string(),
+            "xms" => self.xms.clone(),
+            "xmx" => self.xmx.clone(),
+            "port" => self.port.to_string(),
+            "bind" => self.bind.to_string(),
+            "jar" => self.jar.to_string(),
+            list => self.list(list)?.join(" "),
+        })
+    }
+
+    fn list(&self, name: &str) -> Option<&[String]> {
+        match name {
+            "extra_args" => Some(&self.extra_args),
+            "jvm_flags" => Some(&self.jvm_flags),
+            "game_flags" => Some(&self.game_flags),
+            _ => None,
+        }
+    }
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::BadRequest(message).with_code(ErrorCode::ServerInvalidLaunchCommand)
+}
+
+/// Replace the `{variable}` placeholders of `text`
+fn substitute(text: &str, value: impl Fn(&str) -> Option<String>) -> Result<String, AppError> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}')
+            .ok_or_else(|| invalid(format!("Unclosed variable in \"{text}\"")))?;
+        let name = &rest[start + 1..start + end];
+        result.push_str(&value(name).ok_or_else(|| invalid(format!("Unknown launch variable {{{name}}}")))?);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn known_variable(name: &str) -> Option<String> {
+    VARIABLES.contains(&name).then(String::new)
+}
+
+/// Check a command template without launching anything
+pub fn validate_command(command: &str) -> Result<(), AppError> {
+    if command.split_whitespace().next().is_none() {
+        return Err(invalid("The launch command is empty".into()));
+    }
+    for word in command.split_whitespace() {
+        substitute(word, known_variable)?;
+    }
+    Ok(())
+}
+
+pub fn validate_env(env: &HashMap<String, String>) -> Result<(), AppError> {
+    for (key, value) in env {
+        if key.is_empty() || key.contains(['=', '\0']) {
+            return Err(invalid(format!("Invalid environment variable name \"{key}\"")));
+        }
+        substitute(value, known_variable)?;
+    }
+    Ok(())
+}
+
+/// Split a server's extra arguments the way a shell would, so that quoted arguments keep their spaces
+pub fn split_args(args: &str) -> Result<Vec<String>, AppError> {
+    shell_words::split(args).map_err(|_| invalid(format!("Unbalanced quotes in the extra arguments \"{args}\"")))
+}
+
+/// Program and arguments of a command template
+pub fn render_command(template: &str, vars: &LaunchVars<'_>) -> Result<Vec<String>, AppError> {
+    let mut command = Vec::new();
+    for word in template.split_whitespace() {
+        let list = word.strip_prefix('{')
+            .and_then(|w| w.strip_suffix('}'))
+            .and_then(|name| vars.list(name));
+        match list {
+            Some(items) => command.extend(items.iter().cloned()),
+            None => command.push(substitute(word, |name| vars.value(name))?),
+        }
+    }
+    if command.is_empty() {
+        return Err(invalid("The launch command is empty".into()));
+    }
+    Ok(command)
+}
+
+pub fn render_env(env: &HashMap<String, String>, vars: &LaunchVars<'_>) -> Result<Vec<(String, String)>, AppError> {
+    env.iter()
+        .map(|(key, value)| Ok((key.clone(), substitute(value, |name| vars.value(name))?)))
+        .collect()
+}
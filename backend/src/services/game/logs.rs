@@ -0,0 +1,421 @@
+// Log files
+// Each run of a server writes its output to `logs/console.log` and its standard error to
+// `logs/stderr.log`. When the server starts again the previous run's logs are kept as
+// `logs/console-<start of the run>.log` and `logs/stderr-<...>.log` instead of being truncated, so
+// that the console history can be searched across runs; `install.log` is kept the same way per
+// install.
+// The dated name is chosen when a live log is started and recorded next to it, so a log is known
+// by the same name before and after its rotation.
+// Live logs that grow too large are rotated by their writer when the panel writes them (container
+// output), otherwise by a periodic maintenance, which also compresses the rotated logs and deletes
+// the files of `logs/` older than the server's retention.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::Regex;
+use serde::Serialize;
+
+use super::events::Stream;
+use super::process::{console_log_path, stderr_log_path};
+
+const CURRENT_LOG: &str = "console.log";
+const STDERR_LOG: &str = "stderr.log";
+const INSTALL_LOG: &str = "install.log";
+const ROTATED_PREFIX: &str = "console-";
+/// Live logs larger than this are rotated while the server runs
+pub const MAX_LOG_BYTES: u64 = 50 * 1024 * 1024;
+/// Rotated logs are compressed once left alone this long, so a follower can finish reading them
+const COMPRESS_AFTER: Duration = Duration::from_secs(300);
+/// Logs are searched backwards by blocks of this size
+const READ_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// Name for the rotated copy of `path`, e.g. `console-20260101-120000.log`
+fn rotated_path(path: &Path, written: SystemTime) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let stamp = DateTime::<Local>::from(written).format("%Y%m%d-%H%M%S");
+    let mut name = format!("{stem}-{stamp}");
+    let mut n = 1;
+    while dir.join(format!("{name}.log")).exists() || dir.join(format!("{name}.log.gz")).exists() {
+        name = format!("{stem}-{stamp}-{n}");
+        n += 1;
+    }
+    dir.join(format!("{name}.log"))
+}
+
+/// File recording the dated name of the live log at `path`
+fn segment_marker(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.segment"))
+}
+
+/// Name the live log at `path` is rotated to, which also identifies it until then. It changes
+/// once the log is rotated, which tells its followers.
+pub fn segment_name(path: &Path) -> String {
+    let marker = segment_marker(path);
+    if let Some(name) = std::fs::read_to_string(&marker).ok().filter(|n| is_rotated(n.trim())) {
+        return name.trim().to_string();
+    }
+    // Logs started before the names were recorded get one now
+    let since = std::fs::metadata(path).and_then(|m| m.modified()).unwrap_or_else(|_| SystemTime::now());
+    let name = file_name(&rotated_path(path, since));
+    let _ = std::fs::write(&marker, &name);
+    name
+}
+
+/// Record a new dated name for the live log at `path`
+fn start_segment(path: &Path) -> std::io::Result<()> {
+    std::fs::write(segment_marker(path), file_name(&rotated_path(path, SystemTime::now())))
+}
+
+/// Where the live log at `path` is rotated to
+fn segment_path(path: &Path) -> PathBuf {
+    let target = path.with_file_name(segment_name(path));
+    let mut compressed = target.as_os_str().to_owned();
+    compressed.push(".gz");
+    if target.exists() || Path::new(&compressed).exists() {
+        return rotated_path(path, SystemTime::now());
+    }
+    target
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Keep the log at `path` under its dated name, if it has anything, and start an empty one
+pub fn rotate(path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > 0) {
+        std::fs::rename(path, segment_path(path))?;
+    }
+    File::create(path)?;
+    start_segment(path)
+}
+
+/// Keep the console logs of the previous run and start empty ones; returns the path of the one
+/// for the standard output
+pub fn rotate_console(working_dir: &Path) -> std::io::Result<PathBuf> {
+    rotate(&stderr_log_path(working_dir))?;
+    let path = console_log_path(working_dir);
+    rotate(&path)?;
+    Ok(path)
+}
+
+/// Rotate a log written by a process that can't reopen it: its content is copied, then the file
+/// is emptied once nothing was appended since the copy. Writers append, so they go on at the start
+/// of the emptied file; only lines written between the last check and the emptying are lost.
+fn rotate_in_place(path: &Path) -> std::io::Result<()> {
+    let mut source = File::open(path)?;
+    let mut copy = File::create(segment_path(path))?;
+    let live = std::fs::OpenOptions::new().write(true).open(path)?;
+    loop {
+        std::io::copy(&mut source, &mut copy)?;
+        if live.metadata()?.len() == source.stream_position()? {
+            live.set_len(0)?;
+            break;
+        }
+    }
+    start_segment(path)
+}
+
+/// Replace a rotated log by its gzipped version, keeping its modification date
+fn compress(path: &Path) -> std::io::Result<()> {
+    let modified = std::fs::metadata(path)?.modified()?;
+    let mut target = path.as_os_str().to_owned();
+    target.push(".gz");
+    let target = PathBuf::from(target);
+
+    let mut encoder = GzEncoder::new(File::create(&target)?, Compression::default());
+    std::io::copy(&mut File::open(path)?, &mut encoder)?;
+    let file = encoder.finish()?;
+    file.set_modified(modified)?;
+    std::fs::remove_file(path)
+}
+
+fn is_live(name: &str) -> bool {
+    name == CURRENT_LOG || name == STDERR_LOG || name == INSTALL_LOG
+}
+
+/// Rotate the oversized live logs of a server, except the console logs when `writer_rotates` them,
+/// compress the rotated ones and delete the files last written more than `retention_days` ago
+/// (never, when 0)
+pub fn maintain(working_dir: &Path, retention_days: u32, writer_rotates: bool) -> std::io::Result<()> {
+    let logs_dir = working_dir.join("logs");
+    if !logs_dir.is_dir() {
+        return Ok(());
+    }
+    let live_logs: &[&str] = if writer_rotates { &[INSTALL_LOG] } else { &[CURRENT_LOG, STDERR_LOG, INSTALL_LOG] };
+    for live in live_logs {
+        let path = logs_dir.join(live);
+        if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+            rotate_in_place(&path)?;
+        }
+    }
+
+    let now = SystemTime::now();
+    let cutoff = now.checked_sub(Duration::from_secs(u64::from(retention_days) * 86_400));
+    for entry in std::fs::read_dir(&logs_dir)?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(metadata) = entry.metadata() else { continue };
+        if !metadata.is_file() || is_live(&name) || name.starts_with('.') {
+            continue;
+        }
+        let Ok(modified) = metadata.modified() else { continue };
+        let expired = retention_days > 0 && cutoff.is_some_and(|cutoff| modified < cutoff);
+        let settled = now.duration_since(modified).is_ok_and(|age| age >= COMPRESS_AFTER);
+        if expired {
+            std::fs::remove_file(entry.path())?;
+        } else if settled && is_rotated(&name) && name.ends_with(".log") {
+            compress(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+fn is_rotated(name: &str) -> bool {
+    (name.starts_with(ROTATED_PREFIX) || name.starts_with("stderr-") || name.starts_with("install-"))
+        && (name.ends_with(".log") || name.ends_with(".log.gz"))
+}
+
+/// The last `count` lines of a log file
+pub fn tail_lines(path: &Path, count: usize) -> Vec<String> {
+    let Ok(content) = std::fs::read(path) else { return Vec::new() };
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(count)..].iter().map(|l| l.to_string()).collect()
+}
+
+
+fn decode(line: &[u8]) -> String {
+    String::from_utf8_lossy(line).trim_end_matches('\r').to_string()
+}
+
+/// Lines of a plain log, last first, with the offset each starts at. The file is read backwards
+/// by blocks, so only the lines walked through are loaded.
+struct ReverseLines {
+    file: File,
+    /// Bytes of the file before `start` are not read yet
+    start: u64,
+    /// Bytes from `start` whose lines are not yielded yet
+    pending: Vec<u8>,
+}
+
+impl ReverseLines {
+    /// The lines starting before byte `end`, the whole file if none
+    fn open(path: &Path, end: Option<u64>) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { file, start: end.map_or(len, |end| end.min(len)), pending: Vec::new() })
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = std::io::Result<(u64, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // The last line runs from the last newline of `pending`, its own one left out
+            let body = self.pending.strip_suffix(b"\n").unwrap_or(&self.pending);
+            if let Some(i) = body.iter().rposition(|&b| b == b'\n') {
+                let line = (self.start + i as u64 + 1, decode(&body[i + 1..]));
+                self.pending.truncate(i + 1);
+                return Some(Ok(line));
+            }
+            if self.start == 0 {
+                if self.pending.is_empty() {
+                    return None;
+                }
+                let line = (0, decode(body));
+                self.pending.clear();
+                return Some(Ok(line));
+            }
+
+            let size = READ_CHUNK_BYTES.min(self.start);
+            self.start -= size;
+            let mut block = vec![0; size as usize];
+            let read = self.file.seek(SeekFrom::Start(self.start)).and_then(|_| self.file.read_exact(&mut block));
+            if let Err(e) = read {
+                return Some(Err(e));
+            }
+            block.append(&mut self.pending);
+            self.pending = block;
+        }
+    }
+}
+
+/// The last `keep` lines of a gzipped log accepted by `matches` and starting before byte `end`,
+/// last first. The log is streamed, as it can't be read backwards.
+fn search_compressed(
+    path: &Path,
+    end: Option<u64>,
+    matches: impl Fn(&str) -> bool,
+    keep: usize,
+) -> std::io::Result<Vec<(u64, String)>> {
+    let mut reader = BufReader::new(GzDecoder::new(File::open(path)?));
+    let mut found = VecDeque::with_capacity(keep);
+    let mut offset = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 || end.is_some_and(|end| offset >= end) {
+            break;
+        }
+        let text = decode(line.strip_suffix(b"\n").unwrap_or(&line));
+        if matches(&text) {
+            if found.len() == keep {
+                found.pop_front();
+            }
+            found.push_back((offset, text));
+        }
+        offset += read as u64;
+    }
+    Ok(found.into_iter().rev().collect())
+}
+
+/// Date and number of a rotated log name, in the order they were given
+fn rotation_order(name: &str) -> (&str, u32) {
+    let base = name.trim_end_matches(".gz").trim_end_matches(".log");
+    match base.rsplit_once('-') {
+        // `console-<date>-<time>-<n>`: the time has 6 digits, the number less
+        Some((stamp, n)) if n.len() < 6 => (stamp, n.parse().unwrap_or(0)),
+        _ => (base, 0),
+    }
+}
+
+/// A run's console or standard error log
+struct RunLog {
+    /// Dated name of the log, the same before and after its rotation and compression
+    name: String,
+    path: PathBuf,
+    modified: DateTime<Utc>,
+    compressed: bool,
+    stream: Stream,
+}
+
+/// Console and standard error logs of the server, those of the current run first
+fn runs(working_dir: &Path) -> Vec<RunLog> {
+    let logs_dir = working_dir.join("logs");
+    let Ok(entries) = std::fs::read_dir(&logs_dir) else { return Vec::new() };
+
+    let mut current = Vec::new();
+    let mut runs: Vec<RunLog> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            let modified = entry.metadata().ok()?.modified().ok()?.into();
+            let stream = if file == CURRENT_LOG || file.starts_with(ROTATED_PREFIX) {
+                Stream::Stdout
+            } else if file == STDERR_LOG || file.starts_with("stderr-") {
+                Stream::Stderr
+            } else {
+                return None;
+            };
+            if file == CURRENT_LOG || file == STDERR_LOG {
+                let name = segment_name(&entry.path());
+                current.push(RunLog { name, path: entry.path(), modified, compressed: false, stream });
+                return None;
+            }
+            if !is_rotated(&file) {
+                return None;
+            }
+            let name = file.strip_suffix(".gz").unwrap_or(&file).to_string();
+            Some(RunLog { compressed: name != file, name, path: entry.path(), modified, stream })
+        })
+        .collect();
+    runs.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| rotation_order(&b.name).cmp(&rotation_order(&a.name))));
+    current.sort_by_key(|run| run.stream == Stream::Stderr);
+    runs.splice(0..0, current);
+    runs
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryLine {
+    /// Dated log file name of the run, which the current run also keeps once rotated
+    pub run: String,
+    /// Byte offset of the line in that log
+    pub offset: u64,
+    pub text: String,
+    pub stream: Stream,
+}
+
+/// Where the next, older page starts
+#[derive(Debug, Serialize)]
+pub struct HistoryCursor {
+    pub run: String,
+    pub before: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryPage {
+    /// Oldest first
+    pub lines: Vec<HistoryLine>,
+    pub next: Option<HistoryCursor>,
+}
+
+/// Search filter and position of a history page
+pub struct HistoryQuery<'a> {
+    pub pattern: Option<&'a Regex>,
+    /// Runs whose log was last written before this are left out
+    pub since: Option<DateTime<Utc>>,
+    /// Start from this run instead of the current one
+    pub run: Option<&'a str>,
+    /// Only lines starting before this byte offset of the first run
+    pub before: Option<u64>,
+    pub limit: usize,
+}
+
+/// The `limit` matching lines preceding the position of `query`, going back through older runs
+pub fn search_history(working_dir: &Path, query: &HistoryQuery<'_>) -> HistoryPage {
+    let mut runs = runs(working_dir);
+    if let Some(start) = query.run {
+        let position = runs.iter().position(|r| r.name == start).unwrap_or(runs.len());
+        runs.drain(..position);
+    }
+
+    // One line more than asked tells whether there is a next page
+    let wanted = query.limit + 1;
+    let matches = |text: &str| query.pattern.is_none_or(|p| p.is_match(text));
+    let mut lines = Vec::new();
+    for (index, run) in runs.iter().enumerate() {
+        if lines.len() == wanted {
+            break;
+        }
+        // Runs are not all in date order, the current logs coming first
+        if query.since.is_some_and(|since| run.modified < since) {
+            continue;
+        }
+        let end = if index == 0 { query.before } else { None };
+
+        let found: Vec<(u64, String)> = if run.compressed {
+            search_compressed(&run.path, end, matches, wanted - lines.len()).unwrap_or_default()
+        } else {
+            let Ok(reader) = ReverseLines::open(&run.path, end) else { continue };
+            reader
+                .map_while(Result::ok)
+                .filter(|(_, text)| matches(text))
+                .take(wanted - lines.len())
+                .collect()
+        };
+        lines.extend(found.into_iter().map(|(offset, text)| HistoryLine { run: run.name.clone(), offset, text, stream: run.stream }));
+    }
+
+    let mut next = None;
+    if lines.len() == wanted {
+        lines.pop();
+        next = lines.last().map(|l| HistoryCursor { run: l.run.clone(), before: l.offset });
+    }
+    lines.reverse();
+    HistoryPage { lines, next }
+}
@@ -1,39 +1,113 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
-use tokio::process::{Command, Child};
-use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-
-use tracing::info;
-
-use super::detection::PlayerDetectionPatterns;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch, RwLock};
+
+use tracing::{info, warn};
+
+use super::detection::{GameCommands, PlayerDetectionPatterns};
+use super::events::{ConsoleEvent, PlayerAction, Stream};
+use super::jvm::JvmProfile;
+use super::runtime::{self, JavaRuntime};
+use super::launch::{self, HytaleOptions, LaunchProfile, LaunchVars};
+use super::logs;
+use super::cgroup::{self, ResourceLimits};
+use super::container::{self, ContainerProcess, ContainerSettings, ContainerSpec};
+use super::process::{self, console_log_path, stderr_log_path, GameProcess, NativeProcess};
+use super::state::{ServerState, StateSnapshot, StateTable};
+use super::watchdog::{self, CrashTracker, ProcessExit};
 
 use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use crate::api::servers::models::{countdown_from_column, ServerRow};
 
 /// Manages game server processes
 use crate::core::database::DbPool;
 
+/// Identifies each spawned process so a stale supervisor never acts on a newer run
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How long a running server gets to confirm a world save before a backup is abandoned
+const SAVE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Grace period before a stopping server is killed, for servers without a `stop_timeout`
+pub const DEFAULT_STOP_TIMEOUT_SECS: i64 = 30;
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Tries at removing a server's cgroup after its exit, every `EXIT_POLL_INTERVAL`
+const CGROUP_RELEASE_ATTEMPTS: usize = 40;
+/// Console lines of each server kept in memory and replayed to new console clients
+const CONSOLE_BUFFER_LINES: usize = 1000;
+
+type ConsoleBuffers = Arc<std::sync::Mutex<HashMap<String, VecDeque<ConsoleEvent>>>>;
+/// Servers whose process is being spawned, which happens outside of the processes lock, with
+/// the console channel of the run
+type StartingSet = Arc<std::sync::Mutex<HashMap<String, broadcast::Sender<ConsoleEvent>>>>;
+
+/// Claim on a server being started, released when dropped
+struct StartClaim {
+    starting: StartingSet,
+    server_id: String,
+    log_tx: broadcast::Sender<ConsoleEvent>,
+}
+
+impl Drop for StartClaim {
+    fn drop(&mut self) {
+        self.starting.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.server_id);
+    }
+}
+
+/// How a server is asked to stop, from its settings
+struct StopSettings {
+    command: String,
+    timeout: Duration,
+    /// Seconds before the stop command at which players are warned, in decreasing order
+    countdown: Vec<u32>,
+    say: &'static str,
+}
+
+/// How a server is launched, from its settings and its launch profile
+struct LaunchSettings {
+    command: String,
+    env: HashMap<String, String>,
+    /// Typed options of Hytale servers
+    hytale: Option<HytaleOptions>,
+    jvm_profile: Option<&'static JvmProfile>,
+    /// Installed Java runtime the server uses instead of its java path
+    runtime: Option<JavaRuntime>,
+}
+
 #[derive(Clone)]
 pub struct ProcessManager {
     processes: Arc<RwLock<HashMap<String, ServerProcess>>>,
     pool: Option<DbPool>,
+    crash_tracker: CrashTracker,
+    states: StateTable,
+    /// Last lines of each server's console, kept across runs
+    console_buffers: ConsoleBuffers,
+    starting: StartingSet,
 }
 
 pub struct ServerProcess {
-    child: Option<Child>,
+    /// `None` while installing. Shared so that process I/O never holds the processes lock.
+    process: Option<Arc<GameProcess>>,
+    run_id: u64,
     install_task: Option<tokio::task::AbortHandle>,
-    log_tx: broadcast::Sender<String>,
+    /// The console follower stops once this is dropped
+    _console_owner: Option<Arc<()>>,
+    /// Accounting source for the metrics when the process has a cgroup of its own
+    cgroup: Option<std::path::PathBuf>,
+    log_tx: broadcast::Sender<ConsoleEvent>,
     players: Arc<std::sync::RwLock<HashSet<String>>>,
-    pub last_metrics: Arc<std::sync::RwLock<Option<String>>>,
+    pub last_metrics: Arc<std::sync::RwLock<Option<serde_json::Value>>>,
     pub last_cpu: Arc<std::sync::RwLock<f32>>,
     pub last_cpu_normalized: Arc<std::sync::RwLock<f32>>,
     pub last_memory: Arc<std::sync::RwLock<u64>>,
     pub last_disk: Arc<std::sync::RwLock<u64>>,
     pub working_dir: String,
+    pub game_type: String,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub auth_required: Arc<std::sync::RwLock<bool>>,
     pub max_memory_allocated: u64,
+    recent_logs: Arc<std::sync::RwLock<VecDeque<String>>>,
 }
 
 impl ProcessManager {
@@ -46,93 +120,106 @@ impl ProcessManager {
         tokio::spawn(async move {
             let mut system = sysinfo::System::new_all();
             let mut tick_count: u64 = 0;
+            // Last CPU time reading of each server measured through its cgroup
+            let mut cgroup_cpu: HashMap<String, (u64, std::time::Instant)> = HashMap::new();
             loop {
                 // Refresh first so we have accurate CPU readings even on first iteration
                 system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
                 
                 {
                     let procs = processes_clone.read().await;
+                    cgroup_cpu.retain(|server_id, _| procs.contains_key(server_id));
                     for (server_id, server_proc) in procs.iter() {
-                        if let Some(child) = &server_proc.child {
-                            if let Some(child_id) = child.id() {
-                                let pid = sysinfo::Pid::from_u32(child_id);
-                                if let Some(process) = system.process(pid) {
-                                    let cpu = process.cpu_usage();
-                                    let cores = system.cpus().len() as f32;
-                                    let cpu_normalized = if cores > 0.0 { cpu / cores } else { 0.0 };
-                                    let memory = process.memory(); // in bytes
-                                    let players_list: Vec<String> = server_proc.players.read()
-                                        .map(|p| p.iter().cloned().collect())
-                                        .unwrap_or_default();
-                                    let player_count = players_list.len();
-                                    
-                                    let mut metrics_json = serde_json::json!({
-                                        "cpu": cpu,
-                                        "cpu_normalized": cpu_normalized,
-                                        "memory": memory,
-                                        "memory_limit": server_proc.max_memory_allocated,
-                                        "players": player_count,
-                                        "players_list": players_list
-                                    });
-
-                                    // Calculate disk size every ~30 seconds (15 ticks) OR at tick 0
-                                    let mut disk_size: u64 = 0;
-                                    if tick_count.is_multiple_of(15) {
-                                        let server_path = std::path::PathBuf::from(&server_proc.working_dir);
-                                        // Use optimized calculation if available via shared utils, but here we are in service layer
-                                        // We can use the util we just updated.
-                                        disk_size = crate::utils::files::calculate_dir_size(&server_path).await;
-                                        
-                                        if let Some(obj) = metrics_json.as_object_mut() {
-                                            obj.insert("disk_bytes".to_string(), serde_json::Value::Number(serde_json::Number::from(disk_size)));
-                                        }
-                                        if let Ok(mut disk_cache) = server_proc.last_disk.write() {
-                                            *disk_cache = disk_size;
+                        if let Some(child_id) = server_proc.process.as_deref().map(GameProcess::pid) {
+                            let usage = match &server_proc.cgroup {
+                                Some(path) => cgroup::usage(path).map(|usage| {
+                                    let now = std::time::Instant::now();
+                                    // Percent of one core, like sysinfo
+                                    let cpu = match cgroup_cpu.insert(server_id.clone(), (usage.cpu_usec, now)) {
+                                        Some((previous, at)) => {
+                                            let elapsed = now.duration_since(at).as_micros() as f32;
+                                            usage.cpu_usec.saturating_sub(previous) as f32 / elapsed.max(1.0) * 100.0
                                         }
-                                    } else {
-                                        // Use cached disk value
-                                        if let Ok(disk_cache) = server_proc.last_disk.read() {
-                                            disk_size = *disk_cache;
-                                        }
-                                    }
+                                        None => 0.0,
+                                    };
+                                    (cpu, usage.memory_bytes)
+                                }),
+                                None => system.process(sysinfo::Pid::from_u32(child_id))
+                                    .map(|process| (process.cpu_usage(), process.memory())),
+                            };
+                            if let Some((cpu, memory)) = usage {
+                                let cores = system.cpus().len() as f32;
+                                let cpu_normalized = if cores > 0.0 { cpu / cores } else { 0.0 };
+                                let players_list: Vec<String> = server_proc.players.read()
+                                    .map(|p| p.iter().cloned().collect())
+                                    .unwrap_or_default();
+                                let player_count = players_list.len();
+                                
+                                let mut metrics_json = serde_json::json!({
+                                    "cpu": cpu,
+                                    "cpu_normalized": cpu_normalized,
+                                    "memory": memory,
+                                    "memory_limit": server_proc.max_memory_allocated,
+                                    "players": player_count,
+                                    "players_list": players_list
+                                });
 
-                                    let metrics_msg = format!("[METRICS]: {metrics_json}");
-                                    let _ = server_proc.log_tx.send(metrics_msg.clone());
-                                    if let Ok(mut cache) = server_proc.last_metrics.write() {
-                                        *cache = Some(metrics_msg);
-                                    }
-                                    if let Ok(mut cpu_cache) = server_proc.last_cpu.write() {
-                                        *cpu_cache = cpu;
+                                // Calculate disk size every ~30 seconds (15 ticks) OR at tick 0
+                                let mut disk_size: u64 = 0;
+                                if tick_count.is_multiple_of(15) {
+                                    let server_path = std::path::PathBuf::from(&server_proc.working_dir);
+                                    // Use optimized calculation if available via shared utils, but here we are in service layer
+                                    // We can use the util we just updated.
+                                    disk_size = crate::utils::files::calculate_dir_size(&server_path).await;
+                                    
+                                    if let Some(obj) = metrics_json.as_object_mut() {
+                                        obj.insert("disk_bytes".to_string(), serde_json::Value::Number(serde_json::Number::from(disk_size)));
                                     }
-                                    if let Ok(mut cpu_norm_cache) = server_proc.last_cpu_normalized.write() {
-                                        *cpu_norm_cache = cpu_normalized;
+                                    if let Ok(mut disk_cache) = server_proc.last_disk.write() {
+                                        *disk_cache = disk_size;
                                     }
-                                    if let Ok(mut mem_cache) = server_proc.last_memory.write() {
-                                        *mem_cache = memory;
+                                } else {
+                                    // Use cached disk value
+                                    if let Ok(disk_cache) = server_proc.last_disk.read() {
+                                        disk_size = *disk_cache;
                                     }
+                                }
 
-                                    // Save metrics to DB every 30 seconds (15 ticks)
-                                    if tick_count.is_multiple_of(15) {
-                                        if let Some(pool) = &pool_clone {
-                                            let pool = pool.clone();
-                                            let server_id = server_id.clone();
-                                            let player_count = if let Ok(players) = server_proc.players.read() {
-                                                players.len() as i32
-                                            } else {
-                                                0
-                                            };
-                                            
-                                            tokio::spawn(async move {
-                                                let _ = crate::api::metrics::insert_metric(
-                                                    &pool,
-                                                    &server_id,
-                                                    cpu_normalized as f64,
-                                                    memory as i64,
-                                                    disk_size as i64,
-                                                    player_count,
-                                                ).await;
-                                            });
-                                        }
+                                let _ = server_proc.log_tx.send(ConsoleEvent::Metrics(metrics_json.clone()));
+                                if let Ok(mut cache) = server_proc.last_metrics.write() {
+                                    *cache = Some(metrics_json);
+                                }
+                                if let Ok(mut cpu_cache) = server_proc.last_cpu.write() {
+                                    *cpu_cache = cpu;
+                                }
+                                if let Ok(mut cpu_norm_cache) = server_proc.last_cpu_normalized.write() {
+                                    *cpu_norm_cache = cpu_normalized;
+                                }
+                                if let Ok(mut mem_cache) = server_proc.last_memory.write() {
+                                    *mem_cache = memory;
+                                }
+
+                                // Save metrics to DB every 30 seconds (15 ticks)
+                                if tick_count.is_multiple_of(15) {
+                                    if let Some(pool) = &pool_clone {
+                                        let pool = pool.clone();
+                                        let server_id = server_id.clone();
+                                        let player_count = if let Ok(players) = server_proc.players.read() {
+                                            players.len() as i32
+                                        } else {
+                                            0
+                                        };
+                                        
+                                        tokio::spawn(async move {
+                                            let _ = crate::api::metrics::insert_metric(
+                                                &pool,
+                                                &server_id,
+                                                cpu_normalized as f64,
+                                                memory as i64,
+                                                disk_size as i64,
+                                                player_count,
+                                            ).await;
+                                        });
                                     }
                                 }
                             }
@@ -163,92 +250,155 @@ impl ProcessManager {
         Self {
             processes,
             pool,
+            crash_tracker: CrashTracker::default(),
+            states: StateTable::default(),
+            console_buffers: ConsoleBuffers::default(),
+            starting: StartingSet::default(),
         }
     }
 
-    // ... (is_running, is_installing, is_auth_required, set_auth_required, subscribe_logs, register_installing, broadcast_log, remove - unchanged)
-    pub fn is_running(&self, server_id: &str) -> bool {
-        if let Ok(mut processes) = self.processes.try_write() {
-            if let Some(proc) = processes.get_mut(server_id) {
-                if proc.child.is_none() { return true; }
-                if let Some(child) = &mut proc.child {
-                    match child.try_wait() {
-                        Ok(None) => return true,
-                        Ok(Some(_)) => {
-                            info!("Server {} process has exited, cleaning up", server_id);
-                            processes.remove(server_id);
-                            return false;
-                        }
-                        Err(_) => return false,
-                    }
-                }
-            }
-        }
-        false
+    pub fn pool(&self) -> Option<&DbPool> {
+        self.pool.as_ref()
     }
-    
-    pub fn is_installing(&self, server_id: &str) -> bool {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.child.is_none();
-            }
-        }
-        false
+
+    pub fn crash_tracker(&self) -> &CrashTracker {
+        &self.crash_tracker
     }
 
-    pub fn is_auth_required(&self, server_id: &str) -> bool {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Ok(mut auth) = proc.auth_required.write() {
-                    if *auth {
-                        let auth_file = std::path::Path::new(&proc.working_dir).join("auth.enc");
-                        if auth_file.exists() {
-                            *auth = false;
-                            return false;
-                        }
-                    }
-                    return *auth;
-                }
+    pub fn state(&self, server_id: &str) -> StateSnapshot {
+        self.states.snapshot(server_id)
+    }
+
+    /// State of a server, first clearing an authentication request completed without the
+    /// console reporting it (the credentials file exists)
+    pub async fn current_state(&self, server_id: &str) -> StateSnapshot {
+        let snapshot = self.state(server_id);
+        if snapshot.state != ServerState::AuthRequired {
+            return snapshot;
+        }
+
+        let processes = self.processes.read().await;
+        if let Some(proc) = processes.get(server_id) {
+            if std::path::Path::new(&proc.working_dir).join("auth.enc").exists() {
+                let next = if snapshot.installing { ServerState::Installing } else { ServerState::Running };
+                let _ = self.set_state(server_id, next, &proc.log_tx);
             }
         }
-        false
+        self.state(server_id)
     }
 
-    pub fn set_auth_required(&self, server_id: &str, required: bool) {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Ok(mut auth) = proc.auth_required.write() {
-                    *auth = required;
+    /// Follow the state of a server; the receiver sees every later change
+    pub fn watch_state(&self, server_id: &str) -> watch::Receiver<StateSnapshot> {
+        self.states.subscribe(server_id)
+    }
+
+    /// Move a server to `next` and announce it on its console
+    fn set_state(&self, server_id: &str, next: ServerState, log_tx: &broadcast::Sender<ConsoleEvent>) -> Result<(), AppError> {
+        announce_state(&self.states, server_id, next, log_tx)
+    }
+
+    /// A game server or its installer is active, stopping included
+    pub fn is_running(&self, server_id: &str) -> bool {
+        self.state(server_id).state.is_active()
+    }
+
+    /// Check whether the process of run `run_id` has exited, removing it from the map if so
+    pub async fn poll_exit(&self, server_id: &str, run_id: u64) -> ProcessExit {
+        let exited = {
+            let processes = self.processes.read().await;
+            let Some(proc) = processes.get(server_id).filter(|p| p.run_id == run_id) else {
+                return ProcessExit::Gone;
+            };
+            // `graceful_stop` reaps the processes it stops
+            if self.state(server_id).state == ServerState::Stopping {
+                return ProcessExit::Running;
+            }
+            let Some(process) = proc.process.as_deref() else {
+                return ProcessExit::Gone;
+            };
+            process.try_exit()
+        };
+
+        match exited {
+            None => ProcessExit::Running,
+            Some(exit) => {
+                let mut processes = self.processes.write().await;
+                if processes.get(server_id).is_none_or(|p| p.run_id != run_id) {
+                    return ProcessExit::Gone;
+                }
+                let Some(proc) = processes.remove(server_id) else { return ProcessExit::Gone };
+                info!("Server {} process has exited (code {:?})", server_id, exit.code);
+                self.release_cgroup(server_id);
+                let next = if exit.success() { ServerState::Stopped } else { ServerState::Crashed };
+                let _ = self.set_state(server_id, next, &proc.log_tx);
+                let log_tail = proc.recent_logs.read()
+                    .map(|logs| logs.iter().cloned().collect())
+                    .unwrap_or_default();
+                ProcessExit::Exited {
+                    exit_code: exit.code,
+                    success: exit.success(),
+                    log_tail,
+                    log_tx: proc.log_tx,
                 }
             }
         }
     }
+    
+    pub fn is_installing(&self, server_id: &str) -> bool {
+        self.state(server_id).installing
+    }
 
-    pub fn subscribe_logs(&self, server_id: &str) -> broadcast::Receiver<String> {
-        let (_tx, rx) = broadcast::channel(10000);
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.log_tx.subscribe();
-            }
-        }
-        rx
+    pub async fn set_auth_required(&self, server_id: &str, required: bool) {
+        let processes = self.processes.read().await;
+        let Some(proc) = processes.get(server_id) else { return };
+
+        let snapshot = self.state(server_id);
+        let next = if required {
+            ServerState::AuthRequired
+        } else if snapshot.state != ServerState::AuthRequired {
+            return;
+        } else if snapshot.installing {
+            ServerState::Installing
+        } else {
+            ServerState::Running
+        };
+        let _ = self.set_state(server_id, next, &proc.log_tx);
+    }
+
+    /// The buffered console lines and a receiver for the lines that follow them, none being missed
+    /// or repeated in between
+    pub async fn subscribe_console(&self, server_id: &str) -> (Vec<ConsoleEvent>, broadcast::Receiver<ConsoleEvent>) {
+        let log_tx = match self.processes.read().await.get(server_id) {
+            Some(proc) => Some(proc.log_tx.clone()),
+            None => self.starting.lock().unwrap_or_else(|e| e.into_inner()).get(server_id).cloned(),
+        };
+        let buffers = self.console_buffers.lock().unwrap_or_else(|e| e.into_inner());
+        let backlog = buffers.get(server_id).map(|lines| lines.iter().cloned().collect()).unwrap_or_default();
+        let receiver = match log_tx {
+            Some(log_tx) => log_tx.subscribe(),
+            None => broadcast::channel(1).1,
+        };
+        (backlog, receiver)
     }
 
     pub async fn register_installing(&self, server_id: &str, working_dir: &str, abort_handle: Option<tokio::task::AbortHandle>) -> Result<(), AppError> {
         let mut processes = self.processes.write().await;
-         if processes.contains_key(server_id) {
+         if processes.contains_key(server_id) || self.is_starting(server_id) {
              return Err(AppError::BadRequest("Server already active".into()));
          }
 
-         let (log_tx, _) = broadcast::channel::<String>(10000);
-         let _ = log_tx.send("[STATUS]: installing".to_string());
+         let (log_tx, _) = broadcast::channel::<ConsoleEvent>(10000);
+         self.set_state(server_id, ServerState::Installing, &log_tx)?;
          let players = Arc::new(std::sync::RwLock::new(HashSet::new()));
 
          processes.insert(
              server_id.to_string(),
              ServerProcess { 
-                 child: None,
+                 process: None,
+                 run_id: 0,
                  install_task: abort_handle,
+                 _console_owner: None,
+                 cgroup: None,
                  log_tx, 
                  players,
                  last_metrics: Arc::new(std::sync::RwLock::new(None)),
@@ -257,24 +407,27 @@ impl ProcessManager {
                  last_memory: Arc::new(std::sync::RwLock::new(0)),
                  last_disk: Arc::new(std::sync::RwLock::new(0)),
                  working_dir: working_dir.to_string(),
+                 game_type: String::new(),
                  started_at: Some(chrono::Utc::now()),
-                 auth_required: Arc::new(std::sync::RwLock::new(false)),
                  max_memory_allocated: 0,
+                 recent_logs: Arc::new(std::sync::RwLock::new(VecDeque::new())),
              },
          );
          Ok(())
     }
 
-    pub async fn broadcast_log(&self, server_id: &str, message: String) {
+    pub async fn broadcast_log(&self, server_id: &str, message: String, stream: Stream) {
         let processes = self.processes.read().await;
         if let Some(proc) = processes.get(server_id) {
-            let _ = proc.log_tx.send(message);
+            let _ = proc.log_tx.send(ConsoleEvent::log(message, stream));
         }
     }
 
     pub async fn remove(&self, server_id: &str) {
         let mut processes = self.processes.write().await;
-        processes.remove(server_id);
+        if let Some(proc) = processes.remove(server_id) {
+            let _ = self.set_state(server_id, ServerState::Stopped, &proc.log_tx);
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -290,346 +443,533 @@ impl ProcessManager {
         config: Option<&serde_json::Value>,
         game_type: &str,
         nice_level: i32, // New argument
+        container: Option<&ContainerSettings>,
+        limits: &ResourceLimits,
     ) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
-
-        if processes.contains_key(server_id) {
-            return Err(AppError::BadRequest("Server already running".into()));
-        }
-
-        let java = java_path.unwrap_or("java");
+        let launch_settings = self.launch_settings(server_id, game_type).await?;
+        // Spawning (an image pull, for containers) can take minutes: the processes lock is only
+        // taken to claim the server, then to register its process
+        let claim = self.claim_start(server_id).await?;
+
+        let java = launch_settings.runtime.as_ref()
+            .map(|r| r.java_path.as_str())
+            .or(java_path)
+            .unwrap_or("java");
         let max_mem = max_memory.unwrap_or("8G");
         let final_working_dir = std::path::PathBuf::from(working_dir);
-        let assets_path = "Assets.zip".to_string();
-
-        let mut cmd;
-        #[cfg(unix)]
-        {
-            if nice_level != 0 {
-                cmd = Command::new("nice");
-                cmd.arg("-n").arg(nice_level.to_string());
-                cmd.arg(java);
-            } else {
-                cmd = Command::new(java);
-            }
-        }
-        #[cfg(windows)]
-        {
-            // Windows priority handling is more complex via start command or API
-            // For simplicity, we just run java
-            cmd = Command::new(java);
-        }
-
-        cmd.current_dir(&final_working_dir);
 
         let heap_target_bytes = parse_memory_to_bytes(max_mem);
-        let (xms, xmx) = calculate_jvm_tokens(heap_target_bytes);
-
-        cmd.arg(format!("-Xms{xms}"))
-            .arg(format!("-Xmx{xmx}"))
-            .arg("-Dterminal.jline=true")
-            .arg("-Dterminal.ansi=true");
-
-        if let Some(args) = extra_args {
-            for arg in args.split_whitespace() {
-                cmd.arg(arg);
+        let (xms, xmx) = match launch_settings.jvm_profile {
+            Some(profile) => profile.heap(heap_target_bytes),
+            None => calculate_jvm_tokens(heap_target_bytes),
+        };
+
+        let (bind_ip, port) = match config {
+            Some(cfg) => {
+                let port = cfg.get("port")
+                    .or(cfg.get("Port"))
+                    .and_then(|v| v.as_u64())
+                    .and_then(|p| u16::try_from(p).ok())
+                    .unwrap_or(5520);
+                let bind_ip = cfg.get("bind_address")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("0.0.0.0");
+                (bind_ip, port)
             }
-        }
+            None => ("0.0.0.0", 5520),
+        };
+
+        // Inside a container the image provides Java, the working directory is mounted elsewhere
+        // and the server listens on all interfaces, the address being applied to the published port
+        let jar = match container {
+            Some(_) => container::container_path(&final_working_dir, executable_path),
+            None => executable_path.to_string(),
+        };
+        let (hytale_jvm_flags, game_flags) = match &launch_settings.hytale {
+            Some(options) => {
+                let aot_present = final_working_dir.join(executable_path)
+                    .with_file_name(launch::HYTALE_AOT_CACHE)
+                    .exists();
+                let aot_file = aot_present.then(|| {
+                    std::path::Path::new(&jar).with_file_name(launch::HYTALE_AOT_CACHE).to_string_lossy().into_owned()
+                });
+                options.flags(aot_file)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        let mut jvm_flags = launch_settings.jvm_profile.map(|p| p.flags(heap_target_bytes)).unwrap_or_default();
+        jvm_flags.extend(hytale_jvm_flags);
+        let vars = LaunchVars {
+            java: if container.is_some() { "java" } else { java },
+            xms,
+            xmx,
+            port,
+            bind: if container.is_some() { "0.0.0.0" } else { bind_ip },
+            jar: &jar,
+            extra_args: launch::split_args(extra_args.unwrap_or_default())?,
+            jvm_flags,
+            game_flags,
+        };
+        let mut command = launch::render_command(&launch_settings.command, &vars)?;
+        let env = launch::render_env(&launch_settings.env, &vars)?;
+
+        let log_tx = claim.log_tx.clone();
+        self.set_state(server_id, ServerState::Starting, &log_tx)?;
+
+        let spawned = match container {
+            Some(settings) => {
+                let spec = ContainerSpec {
+                    settings,
+                    working_dir: &final_working_dir,
+                    command,
+                    env,
+                    limits,
+                    memory_bytes: crate::utils::memory::calculate_total_memory(heap_target_bytes),
+                    bind_address: bind_ip,
+                    port,
+                };
+                ContainerProcess::start(server_id, spec).await.map(GameProcess::Container)
+            }
+            None => {
+                #[cfg(unix)]
+                if nice_level != 0 {
+                    command.splice(0..0, ["nice".to_string(), "-n".to_string(), nice_level.to_string()]);
+                }
+                // Windows priority handling is more complex via start command or API
+                // For simplicity, we just run the command
 
-        cmd.arg("-jar")
-            .arg(executable_path)
-            .arg("--assets")
-            .arg(assets_path);
+                let cgroup = cgroup::prepare(server_id, limits);
+                let program = command.remove(0);
+                NativeProcess::spawn(&program, &command, &env, &final_working_dir, cgroup.as_deref()).await.map(GameProcess::Native)
+            }
+        };
+        let process = match spawned {
+            Ok(process) => process,
+            Err(e) => {
+                let _ = self.set_state(server_id, ServerState::Stopped, &log_tx);
+                self.release_cgroup(server_id);
+                return Err(AppError::Internal(format!("Failed to start server: {e}")));
+            }
+        };
 
-        if let Some(cfg) = config {
-            let port = cfg.get("port")
-                .or(cfg.get("Port"))
-                .and_then(|v| v.as_u64())
-                .unwrap_or(5520);
-             
-            let bind_ip = cfg.get("bind_address")
-                .and_then(|v| v.as_str())
-                .unwrap_or("0.0.0.0");
+        info!("Started server {} with PID {}", server_id, process.pid());
 
-            cmd.arg("--bind");
-            cmd.arg(format!("{bind_ip}:{port}"));
-        } else {
-            cmd.arg("--bind");
-            cmd.arg("0.0.0.0:5520");
+        let heap_bytes = parse_memory_to_bytes(max_memory.unwrap_or("1G"));
+        let run = Run {
+            server_id,
+            working_dir,
+            game_type,
+            max_memory_allocated: limits.memory_bytes
+                .unwrap_or_else(|| crate::utils::memory::calculate_total_memory(heap_bytes)),
+            started_at: chrono::Utc::now(),
+        };
+        if let (Some(runtime), Some(required), None) = (&launch_settings.runtime, runtime::required_java(game_type), container) {
+            if runtime.major < i64::from(required) {
+                let message = format!("{} is older than Java {required}, which {game_type} needs", runtime.name);
+                warn!("Server {}: {}", server_id, message);
+                let _ = log_tx.send(ConsoleEvent::Notice { source: "runtime", message });
+            }
         }
+        let mut processes = self.processes.write().await;
+        self.track(&mut processes, run, process, log_tx, (0, 0), HashSet::new());
 
-        cmd.stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        Ok(())
+    }
 
-        let mut child = cmd
-            .spawn()
-            .map_err(|e| AppError::Internal(format!("Failed to start server: {e}")))?;
+    fn is_starting(&self, server_id: &str) -> bool {
+        self.starting.lock().unwrap_or_else(|e| e.into_inner()).contains_key(server_id)
+    }
 
-        info!("Started server {} with PID {:?}", server_id, child.id());
+    /// Reserve a server for a start: it must not be active nor already being started. An exited
+    /// process its supervisor has not reaped yet is replaced.
+    async fn claim_start(&self, server_id: &str) -> Result<StartClaim, AppError> {
+        let mut processes = self.processes.write().await;
 
-        // Create log file
-        let logs_dir = std::path::Path::new(working_dir).join("logs");
-        if !logs_dir.exists() {
-             let _ = std::fs::create_dir_all(&logs_dir);
+        if let Some(existing) = processes.get(server_id) {
+            let exited = existing.process.as_deref().is_some_and(|p| p.try_exit().is_some());
+            if !exited {
+                return Err(AppError::BadRequest("Server already running".into()));
+            }
+            if let Some(proc) = processes.remove(server_id) {
+                let _ = self.set_state(server_id, ServerState::Stopped, &proc.log_tx);
+            }
         }
-        let log_path = logs_dir.join("console.log");
-        
-        let mut log_file = tokio::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(log_path)
-            .await
-            .ok();
-
-        let (log_tx, _) = broadcast::channel::<String>(10000);
-        let _ = log_tx.send("[STATUS]: running".to_string());
-
-        let players = Arc::new(std::sync::RwLock::new(HashSet::new()));
-        let auth_required = Arc::new(std::sync::RwLock::new(false));
-
-        // Spawn task to read stdout (SAME LOGIC AS BEFORE)
-        if let Some(stdout) = child.stdout.take() {
-            let tx = log_tx.clone();
-            let players_clone = players.clone();
-            let server_id_clone = server_id.to_string();
-            let pool_clone_opt = self.pool.clone();
-            let auth_required_clone = auth_required.clone();
-            let game_type_clone = game_type.to_string();
-            
-            tokio::spawn(async move {
-                let mut reader = BufReader::new(stdout).lines();
-                let patterns = PlayerDetectionPatterns::for_game_type(&game_type_clone);
-                let join_re = patterns.join_regex;
-                let leave_re = patterns.leave_regex;
-                let server_started_re = patterns.server_ready_regex;
-                let ip_re = patterns.ip_regex;
-
-                while let Ok(Some(line)) = reader.next_line().await {
-                    if let Some(f) = log_file.as_mut() {
-                        let _ = f.write_all(format!("{line}\n").as_bytes()).await;
-                    }
-
-                    if let Some(caps) = join_re.captures(&line) {
-                        if let Some(name) = caps.get(1) {
-                            let player_name = name.as_str().trim().to_string();
-                            let player_id = caps.get(2).map(|m| m.as_str().to_string());
-                            
-                            info!("Player joined server {}: {} (UUID: {:?})", server_id_clone, player_name, player_id);
-                            if let Ok(mut p) = players_clone.write() {
-                                p.insert(player_name.clone());
-                            }
-                            
-                            if let Some(pool) = &pool_clone_opt {
-                                let pool = pool.clone();
-                                let s_id = server_id_clone.clone();
-                                let p_name = player_name.clone();
-                                let p_id = player_id.clone();
-                                tokio::spawn(async move {
-                                    let now = chrono::Utc::now().to_rfc3339();
-                                    let _ = sqlx::query(
-                                        "INSERT INTO server_players (server_id, player_name, player_id, first_seen, last_seen, is_online) 
-                                         VALUES (?, ?, ?, ?, ?, 1)
-                                         ON CONFLICT(server_id, player_name) DO UPDATE SET 
-                                         last_seen = excluded.last_seen, 
-                                         is_online = 1,
-                                         player_id = COALESCE(excluded.player_id, server_players.player_id)"
-                                    )
-                                    .bind(s_id)
-                                    .bind(p_name)
-                                    .bind(p_id)
-                                    .bind(&now)
-                                    .bind(&now)
-                                    .execute(&pool)
-                                    .await;
-                                });
-                            }
-                        }
-                    } else if let Some(caps) = leave_re.captures(&line) {
-                        if let Some(name) = caps.get(1) {
-                            let player_name = name.as_str().trim().to_string();
-                            if let Ok(mut p) = players_clone.write() {
-                                p.remove(&player_name);
-                            }
-
-                            if let Some(pool) = &pool_clone_opt {
-                                let pool = pool.clone();
-                                let s_id = server_id_clone.clone();
-                                let p_name = player_name.clone();
-                                tokio::spawn(async move {
-                                    let now = chrono::Utc::now().to_rfc3339();
-                                    let _ = sqlx::query(
-                                        "UPDATE server_players SET is_online = 0, last_seen = ? WHERE server_id = ? AND player_name = ?"
-                                    )
-                                    .bind(now)
-                                    .bind(s_id)
-                                    .bind(p_name)
-                                    .execute(&pool)
-                                    .await;
-                                });
-                            }
-                        }
-                    } else if let Some(caps) = ip_re.as_ref().and_then(|re| re.captures(&line)) {
-                        if let (Some(ip), Some(uuid), Some(name)) = (caps.get(1), caps.get(2), caps.get(3)) {
-                            let player_ip = ip.as_str().to_string();
-                            let player_id = uuid.as_str().to_string();
-                            let player_name = name.as_str().trim().to_string();
-                            
-                            if let Some(pool) = &pool_clone_opt {
-                                let pool = pool.clone();
-                                let s_id = server_id_clone.clone();
-                                tokio::spawn(async move {
-                                    let _ = sqlx::query(
-                                        "UPDATE server_players SET player_ip = ?, player_id = ? WHERE server_id = ? AND player_name = ?"
-                                    )
-                                    .bind(player_ip)
-                                    .bind(&player_id)
-                                    .bind(&s_id)
-                                    .bind(&player_name)
-                                    .execute(&pool)
-                                    .await;
-                                });
-                            }
-                        }
-                    } else if server_started_re.is_match(&line) {
-                         let _ = tx.send("[STATUS]: running".to_string());
-                    }
-
-                    if (line.contains("IMPORTANT") && (line.contains("authentifier") || line.contains("authenticate"))) ||
-                       (line.contains("[HytaleServer] No server tokens configured")) ||
-                       (line.contains("/auth login to authenticate")) {
-                         if let Ok(mut auth) = auth_required_clone.write() {
-                             *auth = true;
-                             let _ = tx.send("[STATUS]: auth_required".to_string());
-                         }
-                    }
-
-                    if line.contains("Hytale Server Booted!") {
-                        let _ = tx.send("[STATUS]: booted".to_string());
-                    }
+        let mut starting = self.starting.lock().unwrap_or_else(|e| e.into_inner());
+        if starting.contains_key(server_id) {
+            return Err(AppError::BadRequest("Server already starting".into()));
+        }
+        let (log_tx, _) = broadcast::channel::<ConsoleEvent>(10000);
+        starting.insert(server_id.to_string(), log_tx.clone());
+        Ok(StartClaim { starting: self.starting.clone(), server_id: server_id.to_string(), log_tx })
+    }
 
-                    if line.contains("Authentication successful!") {
-                        if let Ok(mut auth) = auth_required_clone.write() {
-                            *auth = false;
-                            let _ = tx.send("[STATUS]: auth_success".to_string());
-                        }
-                    }
+    /// Take back the game servers left running by a previous panel instance
+    pub async fn reattach(&self) {
+        let Some(pool) = &self.pool else { return };
+        let servers: Vec<ServerRow> = match sqlx::query_as("SELECT * FROM servers").fetch_all(pool).await {
+            Ok(servers) => servers,
+            Err(e) => {
+                warn!("Cannot look for running servers to reattach: {}", e);
+                return;
+            }
+        };
+
+        // Registered together once found; looking for them goes through Docker and the database
+        let mut attached_runs = Vec::new();
+        for server in &servers {
+            let working_dir = std::path::Path::new(&server.working_dir);
+            let attached = match server.container_settings() {
+                Some(_) => ContainerProcess::attach(&server.id, working_dir).await.map(GameProcess::Container),
+                None => NativeProcess::attach(working_dir).map(GameProcess::Native),
+            };
+            let Some(process) = attached else {
+                // Nobody is connected to a server that is not running
+                let _ = sqlx::query("UPDATE server_players SET is_online = 0 WHERE server_id = ?")
+                    .bind(&server.id)
+                    .execute(pool)
+                    .await;
+                continue;
+            };
+            info!("Reattached to server {} (PID {})", server.name, process.pid());
+
+            let log_path = console_log_path(working_dir);
+            let log_len = |path: &std::path::Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            let offsets = (log_len(&log_path), log_len(&stderr_log_path(working_dir)));
+            // What the server wrote before the panel restarted
+            self.console_buffers.lock().unwrap_or_else(|e| e.into_inner())
+                .insert(server.id.clone(), logs::tail_lines(&log_path, CONSOLE_BUFFER_LINES)
+                    .into_iter()
+                    .map(|line| ConsoleEvent::log(line, Stream::Stdout))
+                    .collect());
+            let started_at = match &process {
+                GameProcess::Container(container) => container.started_at(),
+                GameProcess::Native(_) => std::fs::metadata(working_dir.join(process::RUN_DIR))
+                    .and_then(|m| m.modified())
+                    .map(chrono::DateTime::<chrono::Utc>::from)
+                    .ok(),
+            }
+            .unwrap_or_else(chrono::Utc::now);
+            let players: HashSet<String> = sqlx::query_scalar(
+                "SELECT player_name FROM server_players WHERE server_id = ? AND is_online = 1"
+            )
+            .bind(&server.id)
+            .fetch_all(pool)
+            .await
+            .map(|names: Vec<String>| names.into_iter().collect())
+            .unwrap_or_default();
 
-                    let _ = tx.send(line);
-                }
-                
-                info!("Server {} stdout stream ended", server_id_clone);
-                let _ = tx.send("[STATUS]: stopped".to_string());
-                
-                if let Some(f) = log_file.as_mut() {
-                    let _ = f.write_all(b"[Server Stopped]\n").await;
-                    let _ = f.flush().await;
-                }
-            });
+            let (log_tx, _) = broadcast::channel::<ConsoleEvent>(10000);
+            if self.set_state(&server.id, ServerState::Starting, &log_tx).is_err() {
+                continue;
+            }
+            let _ = self.set_state(&server.id, ServerState::Running, &log_tx);
+
+            let heap_bytes = parse_memory_to_bytes(server.max_memory.as_deref().unwrap_or("1G"));
+            let run = Run {
+                server_id: &server.id,
+                working_dir: &server.working_dir,
+                game_type: &server.game_type,
+                max_memory_allocated: server.resource_limits().memory_bytes
+                    .unwrap_or_else(|| crate::utils::memory::calculate_total_memory(heap_bytes)),
+                started_at,
+            };
+            attached_runs.push((run, process, log_tx, offsets, players));
         }
 
-        // Spawn task to read stderr
-        if let Some(stderr) = child.stderr.take() {
-            let tx = log_tx.clone();
-            let server_id_clone = server_id.to_string();
-            let auth_required_clone = auth_required.clone();
+        let mut processes = self.processes.write().await;
+        for (run, process, log_tx, offsets, players) in attached_runs {
+            self.track(&mut processes, run, process, log_tx, offsets, players);
+        }
+    }
 
+    /// Register a game process: follow its console logs from the byte `log_offsets` (standard output,
+    /// standard error) and supervise it
+    fn track(
+        &self,
+        processes: &mut HashMap<String, ServerProcess>,
+        run: Run<'_>,
+        process: GameProcess,
+        log_tx: broadcast::Sender<ConsoleEvent>,
+        log_offsets: (u64, u64),
+        players: HashSet<String>,
+    ) {
+        let players = Arc::new(std::sync::RwLock::new(players));
+        let recent_logs = Arc::new(std::sync::RwLock::new(VecDeque::with_capacity(watchdog::LOG_TAIL_LINES)));
+        let console_owner = Arc::new(());
+
+        let console = Arc::new(Console {
+            server_id: run.server_id.to_string(),
+            pool: self.pool.clone(),
+            states: self.states.clone(),
+            tx: log_tx.clone(),
+            players: players.clone(),
+            recent_logs: recent_logs.clone(),
+            buffers: self.console_buffers.clone(),
+            patterns: PlayerDetectionPatterns::for_game_type(run.game_type),
+        });
+        let working_dir = std::path::Path::new(run.working_dir);
+        let logs = [
+            (console_log_path(working_dir), log_offsets.0, Stream::Stdout),
+            (stderr_log_path(working_dir), log_offsets.1, Stream::Stderr),
+        ];
+        for (log_path, offset, stream) in logs {
+            let owner = Arc::downgrade(&console_owner);
+            let console = console.clone();
+            let server_id = run.server_id.to_string();
             tokio::spawn(async move {
-                let mut reader = BufReader::new(stderr).lines();
-                while let Ok(Some(line)) = reader.next_line().await {
-                    let _ = tx.send(line.clone());
-                    
-                     if (line.contains("IMPORTANT") && (line.contains("authentifier") || line.contains("authenticate"))) ||
-                       (line.contains("[HytaleServer] No server tokens configured")) ||
-                       (line.contains("/auth login to authenticate")) {
-                            if let Ok(mut auth) = auth_required_clone.write() {
-                             *auth = true;
-                             let _ = tx.send("[STATUS]: auth_required".to_string());
-                         }
-                    }
-                }
-                info!("Server {} stderr stream ended", server_id_clone);
+                process::follow_console(log_path, offset, owner, |line| console.handle_line(line, stream)).await;
+                info!("Server {} console stream ended ({:?})", server_id, stream);
             });
         }
 
-        let heap_bytes = parse_memory_to_bytes(max_memory.unwrap_or("1G"));
-        let total_memory_bytes = crate::utils::memory::calculate_total_memory(heap_bytes);
-
+        let run_id = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+        let cgroup = cgroup::of_process(process.pid());
         processes.insert(
-            server_id.to_string(),
-            ServerProcess { 
-                child: Some(child), 
+            run.server_id.to_string(),
+            ServerProcess {
+                process: Some(Arc::new(process)),
+                run_id,
                 install_task: None,
-                log_tx, 
+                _console_owner: Some(console_owner),
+                cgroup,
+                log_tx,
                 players,
                 last_metrics: Arc::new(std::sync::RwLock::new(None)),
                 last_cpu: Arc::new(std::sync::RwLock::new(0.0)),
                 last_cpu_normalized: Arc::new(std::sync::RwLock::new(0.0)),
                 last_memory: Arc::new(std::sync::RwLock::new(0)),
                 last_disk: Arc::new(std::sync::RwLock::new(0)),
-                working_dir: working_dir.to_string(),
-                started_at: Some(chrono::Utc::now()),
-                auth_required,
-                max_memory_allocated: total_memory_bytes,
+                working_dir: run.working_dir.to_string(),
+                game_type: run.game_type.to_string(),
+                started_at: Some(run.started_at),
+                max_memory_allocated: run.max_memory_allocated,
+                recent_logs,
             },
         );
 
-        Ok(())
+        watchdog::spawn_supervisor(self.clone(), run.server_id.to_string(), run_id);
     }
 
     pub async fn stop(&self, server_id: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
+        self.graceful_stop(server_id, "stopping").await
+    }
 
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+    /// Warn the players, send the stop command and wait for the process to exit, killing it once
+    /// the grace timeout is over. The processes lock is only taken for short checks, never while waiting.
+    async fn graceful_stop(&self, server_id: &str, action: &str) -> Result<(), AppError> {
+        let (run_id, game_type) = {
+            let mut processes = self.processes.write().await;
+            let proc = processes
+                .get_mut(server_id)
+                .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+
+            if proc.process.is_none() {
+                if let Some(task) = &proc.install_task {
+                    task.abort();
+                    info!("Aborted installation task for server {}", server_id);
+                }
+                if let Some(proc) = processes.remove(server_id) {
+                    let _ = self.set_state(server_id, ServerState::Stopped, &proc.log_tx);
+                }
+                return Ok(());
+            }
+            // Also keeps the supervisor from mistaking this exit for a crash
+            self.set_state(server_id, ServerState::Stopping, &proc.log_tx)?;
+            (proc.run_id, proc.game_type.clone())
+        };
+
+        let settings = self.stop_settings(server_id, &game_type).await;
+        let mut exited = false;
+
+        for (i, &secs) in settings.countdown.iter().enumerate() {
+            let _ = self.send_command(server_id, &format!("{} Server {action} in {secs}s", settings.say)).await;
+            let next = settings.countdown.get(i + 1).copied().unwrap_or(0);
+            if self.wait_exit(server_id, run_id, Duration::from_secs(u64::from(secs - next))).await {
+                exited = true;
+                break;
+            }
+        }
 
-        if let Some(task) = &proc.install_task {
-            task.abort();
-            info!("Aborted installation task for server {}", server_id);
+        if !exited {
+            let _ = self.send_command(server_id, &settings.command).await;
+            exited = self.wait_exit(server_id, run_id, settings.timeout).await;
         }
 
-        if let Some(child) = &mut proc.child {
-            // Try graceful shutdown first
-            if let Some(stdin) = child.stdin.as_mut() {
-                let _ = stdin.write_all(b"/shutdown\n").await;
-                let _ = stdin.flush().await;
+        if !exited {
+            let running = self.processes.read().await
+                .get(server_id)
+                .filter(|p| p.run_id == run_id)
+                .map(|p| (p.process.clone(), p.log_tx.clone()));
+            if let Some((Some(process), log_tx)) = running {
+                warn!("Server {} did not stop within {}s, killing it", server_id, settings.timeout.as_secs());
+                if let Err(e) = process.kill().await {
+                    let _ = self.set_state(server_id, ServerState::Running, &log_tx);
+                    return Err(AppError::Internal(format!("Failed to kill server: {e}")));
+                }
             }
+        }
 
-            // Wait a bit for graceful shutdown
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-
-            // Force kill if still running
-            if child.try_wait().map_err(|e| AppError::Internal(e.to_string()))?.is_none() {
-                child
-                    .kill()
-                    .await
-                    .map_err(|e| AppError::Internal(format!("Failed to kill server: {e}")))?;
+        {
+            let mut processes = self.processes.write().await;
+            if processes.get(server_id).is_some_and(|p| p.run_id == run_id) {
+                if let Some(proc) = processes.remove(server_id) {
+                    let _ = self.set_state(server_id, ServerState::Stopped, &proc.log_tx);
+                }
             }
         }
-        
-        processes.remove(server_id);
+
+        self.crash_tracker.clear(server_id);
+        self.release_cgroup(server_id);
         info!("Stopped server {}", server_id);
 
         Ok(())
     }
 
-    pub async fn kill(&self, server_id: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
+    /// Remove the cgroup of a server in the background, once the last of its processes (the wrapper
+    /// exits after the game) is gone; left alone if the server is started again meanwhile
+    pub fn release_cgroup(&self, server_id: &str) {
+        let manager = self.clone();
+        let server_id = server_id.to_string();
+        tokio::spawn(async move {
+            for _ in 0..CGROUP_RELEASE_ATTEMPTS {
+                if manager.processes.read().await.contains_key(&server_id) {
+                    return;
+                }
+                if !manager.is_starting(&server_id) && cgroup::remove(&server_id).is_ok() {
+                    return;
+                }
+                tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+            }
+            warn!("Cannot remove the cgroup of server {}, processes are still in it", server_id);
+        });
+    }
 
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+    /// Wait up to `timeout` for the process of run `run_id` to exit. True once it has exited or was replaced.
+    async fn wait_exit(&self, server_id: &str, run_id: u64, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            {
+                let processes = self.processes.read().await;
+                let Some(process) = processes.get(server_id)
+                    .filter(|p| p.run_id == run_id)
+                    .and_then(|p| p.process.as_deref())
+                else {
+                    return true;
+                };
+                if process.try_exit().is_some() {
+                    return true;
+                }
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        }
+    }
 
-        if let Some(child) = &mut proc.child {
-            child
+    /// Command template and environment of a server: its own template if it has one, otherwise the
+    /// launch profile of its game type, whose environment the server's extends
+    async fn launch_settings(&self, server_id: &str, game_type: &str) -> Result<LaunchSettings, AppError> {
+        let Some(pool) = &self.pool else {
+            return Ok(LaunchSettings {
+                command: launch::HYTALE_COMMAND.to_string(),
+                env: HashMap::new(),
+                hytale: None,
+                jvm_profile: None,
+                runtime: None,
+            });
+        };
+        let server: Option<ServerRow> = sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+            .bind(server_id)
+            .fetch_optional(pool)
+            .await?;
+
+        let profile = launch::load_profile(pool, game_type).await?;
+        let mut env = profile.as_ref().map(LaunchProfile::env).unwrap_or_default();
+        env.extend(launch::env_from_column(server.as_ref().and_then(|s| s.launch_env.as_deref())));
+
+        let command = server.as_ref()
+            .and_then(|s| s.launch_command.clone())
+            .filter(|c| !c.trim().is_empty())
+            .or_else(|| profile.map(|p| p.command))
+            .unwrap_or_else(|| launch::HYTALE_COMMAND.to_string());
+        let jvm_profile = server.as_ref().and_then(ServerRow::jvm_profile);
+        let runtime = match server.as_ref().and_then(|s| s.java_runtime.as_deref()).filter(|r| !r.is_empty()) {
+            Some(id) => Some(runtime::load(pool, id).await?.ok_or_else(runtime::not_found)?),
+            None => None,
+        };
+        let hytale = server
+            .filter(|_| game_type.eq_ignore_ascii_case("hytale"))
+            .map(|s| s.hytale_options());
+        Ok(LaunchSettings { command, env, hytale, jvm_profile, runtime })
+    }
+
+    async fn stop_settings(&self, server_id: &str, game_type: &str) -> StopSettings {
+        let commands = GameCommands::for_game_type(game_type);
+        // The server's stop command, else its launch profile's, else the game's default
+        type StopRow = (Option<String>, i64, Option<String>, Option<String>);
+        let row: Option<StopRow> = match &self.pool {
+            Some(pool) => sqlx::query_as(
+                "SELECT s.stop_command, s.stop_timeout, s.stop_countdown, p.stop_command
+                 FROM servers s LEFT JOIN launch_profiles p ON p.game_type = LOWER(s.game_type)
+                 WHERE s.id = ?"
+            )
+                .bind(server_id)
+                .fetch_optional(pool)
+                .await
+                .ok()
+                .flatten(),
+            None => None,
+        };
+        let (command, timeout, countdown, profile_command) = row.unwrap_or((None, DEFAULT_STOP_TIMEOUT_SECS, None, None));
+
+        let mut countdown = countdown_from_column(countdown.as_deref());
+        countdown.sort_unstable_by(|a, b| b.cmp(a));
+        countdown.dedup();
+        countdown.retain(|&secs| secs > 0);
+
+        StopSettings {
+            command: command
+                .filter(|c| !c.trim().is_empty())
+                .or(profile_command.filter(|c| !c.trim().is_empty()))
+                .unwrap_or_else(|| commands.stop.to_string()),
+            timeout: Duration::from_secs(timeout.max(1) as u64),
+            countdown,
+            say: commands.say,
+        }
+    }
+
+    pub async fn kill(&self, server_id: &str) -> Result<(), AppError> {
+        let (process, run_id, install_task) = {
+            let processes = self.processes.read().await;
+            let proc = processes
+                .get(server_id)
+                .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+            (proc.process.clone(), proc.run_id, proc.install_task.clone())
+        };
+
+        match process {
+            Some(process) => process
                 .kill()
                 .await
-                .map_err(|e| AppError::Internal(format!("Failed to kill server: {e}")))?;
+                .map_err(|e| AppError::Internal(format!("Failed to kill server: {e}")))?,
+            // Installing: there is no process yet, only the installation to stop
+            None => {
+                if let Some(task) = install_task {
+                    task.abort();
+                    info!("Aborted installation task for server {}", server_id);
+                }
+            }
         }
 
-        processes.remove(server_id);
+        let mut processes = self.processes.write().await;
+        if processes.get(server_id).is_some_and(|p| p.run_id == run_id) {
+            if let Some(proc) = processes.remove(server_id) {
+                let _ = self.set_state(server_id, ServerState::Stopped, &proc.log_tx);
+            }
+        }
+        self.crash_tracker.clear(server_id);
+        self.release_cgroup(server_id);
         info!("Killed server {}", server_id);
 
         Ok(())
@@ -648,11 +988,12 @@ impl ProcessManager {
         config: Option<&serde_json::Value>,
         game_type: &str,
         nice_level: i32,
+        container: Option<&ContainerSettings>,
+        limits: &ResourceLimits,
     ) -> Result<(), AppError> {
         if self.is_running(server_id) {
             info!("Restart: Stopping server {}...", server_id);
-            self.stop(server_id).await?;
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            self.graceful_stop(server_id, "restarting").await?;
         }
 
         let mut retry = 0;
@@ -674,28 +1015,102 @@ impl ProcessManager {
             config,
             game_type,
             nice_level,
+            container,
+            limits,
         )
         .await
     }
 
     // ... (rest of methods like send_command, getters - unchanged)
     pub async fn send_command(&self, server_id: &str, command: &str) -> Result<(), AppError> {
-        let mut processes = self.processes.write().await;
+        let process = self.processes.read().await
+            .get(server_id)
+            .ok_or_else(|| AppError::NotFound("Server not running".into()))?
+            .process
+            .clone();
+
+        if let Some(process) = process {
+            process.send_line(command)
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to send command: {e}")))?;
+        }
 
-        let proc = processes
-            .get_mut(server_id)
-            .ok_or_else(|| AppError::NotFound("Server not running".into()))?;
+        Ok(())
+    }
+
+    /// Flush the world to disk, then run `task` (typically archiving the server directory)
+    /// with automatic saving paused where the game supports it. A server that is not running is
+    /// left alone. The task is not run when the save is not confirmed in time, or when the game
+    /// has no known save confirmation.
+    pub async fn with_saving_paused<F, T>(&self, server_id: &str, task: F) -> Result<T, AppError>
+    where
+        F: std::future::Future<Output = Result<T, AppError>>,
+    {
+        // Subscribe before sending anything so the confirmation cannot be missed
+        let running = {
+            let processes = self.processes.read().await;
+            processes.get(server_id)
+                .filter(|p| p.process.is_some())
+                .map(|p| (p.game_type.clone(), p.log_tx.subscribe()))
+        };
+        let Some((game_type, mut logs)) = running else {
+            return task.await;
+        };
+        let mut state = self.watch_state(server_id);
+
+        let commands = GameCommands::for_game_type(&game_type);
+        let Some(save_complete_re) = PlayerDetectionPatterns::for_game_type(&game_type).save_complete_regex else {
+            return Err(AppError::Internal(format!(
+                "Server {server_id} ({game_type}) does not confirm world saves; stop it to back it up"
+            )).with_code(ErrorCode::BackupSaveUnconfirmed));
+        };
+
+        if let Some(command) = commands.disable_autosave {
+            self.send_command(server_id, command).await?;
+        }
+
+        let saved = async {
+            self.send_command(server_id, commands.save).await?;
+            let stopped = || AppError::Internal(format!("Server {server_id} stopped while saving the world"))
+                .with_code(ErrorCode::BackupSaveTimeout);
+            loop {
+                tokio::select! {
+                    line = logs.recv() => match line {
+                        Ok(ConsoleEvent::Log { line, .. }) if save_complete_re.is_match(&line) => return Ok(()),
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return Err(stopped()),
+                    },
+                    changed = state.changed() => {
+                        let state = state.borrow().state;
+                        if changed.is_err() || matches!(state, ServerState::Stopping | ServerState::Crashed | ServerState::Stopped) {
+                            return Err(stopped());
+                        }
+                    }
+                }
+            }
+        };
 
-        if let Some(child) = &mut proc.child {
-            if let Some(stdin) = child.stdin.as_mut() {
-                stdin.write_all(format!("{command}\n").as_bytes())
-                    .await
-                    .map_err(|e| AppError::Internal(format!("Failed to send command: {e}")))?;
-                stdin.flush().await.map_err(|e| AppError::Internal(format!("Failed to flush stdin: {e}")))?;
+        let result = match tokio::time::timeout(SAVE_TIMEOUT, saved).await {
+            Ok(Ok(())) => {
+                if commands.disable_autosave.is_some() {
+                    info!("Server {} saved its world, saving paused for backup", server_id);
+                }
+                task.await
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(AppError::Internal(format!(
+                "Server {server_id} did not confirm the world save within {}s",
+                SAVE_TIMEOUT.as_secs()
+            )).with_code(ErrorCode::BackupSaveTimeout)),
+        };
+
+        if let Some(command) = commands.enable_autosave {
+            if let Err(e) = self.send_command(server_id, command).await {
+                warn!("Failed to resume saving on server {}: {}", server_id, e);
             }
         }
 
-        Ok(())
+        result
     }
 
     pub async fn get_online_players(&self, server_id: &str) -> Option<Vec<String>> {
@@ -709,12 +1124,8 @@ impl ProcessManager {
     }
 
     pub async fn get_server_started_at(&self, server_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                return proc.started_at;
-            }
-        }
-        None
+        let processes = self.processes.read().await;
+        processes.get(server_id).and_then(|proc| proc.started_at)
     }
 
     pub async fn get_total_online_players(&self) -> u32 {
@@ -729,17 +1140,11 @@ impl ProcessManager {
     }
 
     pub async fn get_server_pid(&self, server_id: &str) -> Option<u32> {
-        if let Ok(processes) = self.processes.try_read() {
-            if let Some(proc) = processes.get(server_id) {
-                if let Some(child) = &proc.child {
-                    return child.id();
-                }
-            }
-        }
-        None
+        let processes = self.processes.read().await;
+        processes.get(server_id).and_then(|proc| proc.process.as_deref()).map(GameProcess::pid)
     }
 
-    pub async fn get_last_metrics(&self, server_id: &str) -> Option<String> {
+    pub async fn get_last_metrics(&self, server_id: &str) -> Option<serde_json::Value> {
         let processes = self.processes.read().await;
         if let Some(proc) = processes.get(server_id) {
             if let Ok(cache) = proc.last_metrics.read() {
@@ -766,6 +1171,169 @@ impl ProcessManager {
     }
 }
 
+/// Identity of a game process being registered
+struct Run<'a> {
+    server_id: &'a str,
+    working_dir: &'a str,
+    game_type: &'a str,
+    max_memory_allocated: u64,
+    started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Reacts to console lines: player tracking, state changes and broadcasting
+struct Console {
+    server_id: String,
+    pool: Option<DbPool>,
+    states: StateTable,
+    tx: broadcast::Sender<ConsoleEvent>,
+    players: Arc<std::sync::RwLock<HashSet<String>>>,
+    recent_logs: Arc<std::sync::RwLock<VecDeque<String>>>,
+    buffers: ConsoleBuffers,
+    patterns: PlayerDetectionPatterns,
+}
+
+impl Console {
+    fn handle_line(&self, line: String, stream: Stream) {
+        let server_id = &self.server_id;
+        let patterns = &self.patterns;
+
+        if let Some(caps) = patterns.join_regex.captures(&line) {
+            if let Some(name) = caps.get(1) {
+                let player_name = name.as_str().trim().to_string();
+                let player_id = caps.get(2).map(|m| m.as_str().to_string());
+
+                info!("Player joined server {}: {} (UUID: {:?})", server_id, player_name, player_id);
+                let _ = self.tx.send(ConsoleEvent::Player { action: PlayerAction::Join, name: player_name.clone() });
+                if let Ok(mut p) = self.players.write() {
+                    p.insert(player_name.clone());
+                }
+
+                if let Some(pool) = &self.pool {
+                    let pool = pool.clone();
+                    let s_id = server_id.clone();
+                    tokio::spawn(async move {
+                        let now = chrono::Utc::now().to_rfc3339();
+                        let _ = sqlx::query(
+                            "INSERT INTO server_players (server_id, player_name, player_id, first_seen, last_seen, is_online) 
+                             VALUES (?, ?, ?, ?, ?, 1)
+                             ON CONFLICT(server_id, player_name) DO UPDATE SET 
+                             last_seen = excluded.last_seen, 
+                             is_online = 1,
+                             player_id = COALESCE(excluded.player_id, server_players.player_id)"
+                        )
+                        .bind(s_id)
+                        .bind(player_name)
+                        .bind(player_id)
+                        .bind(&now)
+                        .bind(&now)
+                        .execute(&pool)
+                        .await;
+                    });
+                }
+            }
+        } else if let Some(caps) = patterns.leave_regex.captures(&line) {
+            if let Some(name) = caps.get(1) {
+                let player_name = name.as_str().trim().to_string();
+                if let Ok(mut p) = self.players.write() {
+                    p.remove(&player_name);
+                }
+                let _ = self.tx.send(ConsoleEvent::Player { action: PlayerAction::Leave, name: player_name.clone() });
+
+                if let Some(pool) = &self.pool {
+                    let pool = pool.clone();
+                    let s_id = server_id.clone();
+                    tokio::spawn(async move {
+                        let now = chrono::Utc::now().to_rfc3339();
+                        let _ = sqlx::query(
+                            "UPDATE server_players SET is_online = 0, last_seen = ? WHERE server_id = ? AND player_name = ?"
+                        )
+                        .bind(now)
+                        .bind(s_id)
+                        .bind(player_name)
+                        .execute(&pool)
+                        .await;
+                    });
+                }
+            }
+        } else if let Some(caps) = patterns.ip_regex.as_ref().and_then(|re| re.captures(&line)) {
+            if let (Some(ip), Some(uuid), Some(name)) = (caps.get(1), caps.get(2), caps.get(3)) {
+                let player_ip = ip.as_str().to_string();
+                let player_id = uuid.as_str().to_string();
+                let player_name = name.as_str().trim().to_string();
+
+                if let Some(pool) = &self.pool {
+                    let pool = pool.clone();
+                    let s_id = server_id.clone();
+                    tokio::spawn(async move {
+                        let _ = sqlx::query(
+                            "UPDATE server_players SET player_ip = ?, player_id = ? WHERE server_id = ? AND player_name = ?"
+                        )
+                        .bind(player_ip)
+                        .bind(&player_id)
+                        .bind(&s_id)
+                        .bind(&player_name)
+                        .execute(&pool)
+                        .await;
+                    });
+                }
+            }
+        } else if patterns.server_ready_regex.is_match(&line) {
+            // Readiness does not clear a pending authentication request
+            if self.states.snapshot(server_id).state == ServerState::Starting {
+                let _ = announce_state(&self.states, server_id, ServerState::Running, &self.tx);
+            }
+        }
+
+        if (line.contains("IMPORTANT") && (line.contains("authentifier") || line.contains("authenticate"))) ||
+           (line.contains("[HytaleServer] No server tokens configured")) ||
+           (line.contains("/auth login to authenticate")) {
+            let _ = announce_state(&self.states, server_id, ServerState::AuthRequired, &self.tx);
+        }
+
+        if line.contains("Hytale Server Booted!") {
+            let _ = self.tx.send(ConsoleEvent::Status("booted".to_string()));
+            if self.states.snapshot(server_id).state == ServerState::Starting {
+                let _ = announce_state(&self.states, server_id, ServerState::Running, &self.tx);
+            }
+        }
+
+        if line.contains("Authentication successful!")
+            && self.states.snapshot(server_id).state == ServerState::AuthRequired
+        {
+            let _ = self.tx.send(ConsoleEvent::Status("auth_success".to_string()));
+            let _ = announce_state(&self.states, server_id, ServerState::Running, &self.tx);
+        }
+
+        push_recent_log(&self.recent_logs, &line);
+        let event = ConsoleEvent::log(line, stream);
+        // Sent under the lock so that `subscribe_console` sees each line exactly once
+        let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        let buffer = buffers.entry(self.server_id.clone()).or_default();
+        if buffer.len() >= CONSOLE_BUFFER_LINES {
+            buffer.pop_front();
+        }
+        buffer.push_back(event.clone());
+        let _ = self.tx.send(event);
+    }
+}
+
+/// Move a server to `next` and announce it to its log subscribers when the state changed
+fn announce_state(states: &StateTable, server_id: &str, next: ServerState, log_tx: &broadcast::Sender<ConsoleEvent>) -> Result<(), AppError> {
+    if states.transition(server_id, next)? {
+        let _ = log_tx.send(next.event());
+    }
+    Ok(())
+}
+
+fn push_recent_log(buffer: &std::sync::RwLock<VecDeque<String>>, line: &str) {
+    if let Ok(mut logs) = buffer.write() {
+        if logs.len() >= watchdog::LOG_TAIL_LINES {
+            logs.pop_front();
+        }
+        logs.push_back(line.to_string());
+    }
+}
+
 impl Default for ProcessManager {
     fn default() -> Self {
         Self::new(None)
@@ -1,4 +1,14 @@
 pub mod manager;
+pub mod cgroup;
+pub mod container;
 pub mod detection;
+pub mod events;
+pub mod jvm;
+pub mod launch;
+pub mod logs;
+pub mod process;
+pub mod runtime;
+pub mod state;
+pub mod watchdog;
 
 pub use manager::ProcessManager;
@@ -0,0 +1,420 @@
+// Detached game processes
+// On Unix a game server runs under a small shell wrapper in its own process group, so it outlives
+// the panel. Its console goes through files in `<working_dir>/.draveur`: a FIFO for stdin, plus the
+// PIDs and the exit code, while the output is appended to the console log. A restarted panel finds
+// the PIDs there and reattaches. Elsewhere the server is a plain child process writing to the same log.
+// Containerized servers (see `container`) write to that log as well.
+
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Weak;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+use super::container::ContainerProcess;
+use super::logs;
+use crate::core::error::AppError;
+
+/// Panel files kept in a server's working directory; never backed up
+pub const RUN_DIR: &str = ".draveur";
+const PID_FILE: &str = "pid";
+const EXIT_FILE: &str = "exit";
+#[cfg(unix)]
+const STDIN_FIFO: &str = "stdin";
+
+/// Joins the server's cgroup if it has one, runs the game server with stdin on the FIFO (opened
+/// read-write so it never sees EOF when the panel goes away) and its output in the console logs, and
+/// records the game and wrapper PIDs, then the exit code
+#[cfg(unix)]
+const WRAPPER_SCRIPT: &str = r#"[ -z "$DRAVEUR_CGROUP" ] || echo $$ > "$DRAVEUR_CGROUP/cgroup.procs"
+"$@" 0<>"$DRAVEUR_RUN/stdin" >>"$DRAVEUR_LOG" 2>>"$DRAVEUR_ERR_LOG" &
+echo "$! $$" > "$DRAVEUR_RUN/pid.tmp" && mv "$DRAVEUR_RUN/pid.tmp" "$DRAVEUR_RUN/pid"
+wait $!
+code=$?
+echo "[Server Stopped]" >> "$DRAVEUR_LOG"
+echo $code > "$DRAVEUR_RUN/exit.tmp" && mv "$DRAVEUR_RUN/exit.tmp" "$DRAVEUR_RUN/exit""#;
+
+/// How long the wrapper gets to report the game PID
+#[cfg(unix)]
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(5);
+const TAIL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub fn console_log_path(working_dir: &Path) -> PathBuf {
+    working_dir.join("logs").join("console.log")
+}
+
+/// Where the game's standard error goes, the standard output going to the console log
+pub fn stderr_log_path(working_dir: &Path) -> PathBuf {
+    working_dir.join("logs").join("stderr.log")
+}
+
+#[derive(Clone, Copy)]
+pub struct Exit {
+    /// `None` when the process was killed without its wrapper recording anything
+    pub code: Option<i32>,
+}
+
+impl Exit {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// A running game server, whichever way it was started
+pub enum GameProcess {
+    Native(NativeProcess),
+    Container(ContainerProcess),
+}
+
+impl GameProcess {
+    pub fn pid(&self) -> u32 {
+        match self {
+            GameProcess::Native(process) => process.pid(),
+            GameProcess::Container(process) => process.pid(),
+        }
+    }
+
+    /// `Some` once the process has exited
+    pub fn try_exit(&self) -> Option<Exit> {
+        match self {
+            GameProcess::Native(process) => process.try_exit(),
+            GameProcess::Container(process) => process.try_exit(),
+        }
+    }
+
+    /// Write a line to the server's console. Only the console input is locked meanwhile, so a
+    /// blocked write never keeps the process from being polled or killed.
+    pub async fn send_line(&self, line: &str) -> std::io::Result<()> {
+        match self {
+            GameProcess::Native(process) => process.send_line(line).await,
+            GameProcess::Container(process) => process.send_line(line).await,
+        }
+    }
+
+    pub async fn kill(&self) -> std::io::Result<()> {
+        match self {
+            GameProcess::Native(process) => process.kill().await,
+            GameProcess::Container(process) => process.kill().await,
+        }
+    }
+}
+
+pub struct NativeProcess {
+    pid: u32,
+    #[cfg(unix)]
+    wrapper_pid: u32,
+    /// Start time of the wrapper, telling it apart from a process that reuses its PID later
+    #[cfg(unix)]
+    wrapper_started: Option<u64>,
+    #[cfg(unix)]
+    run_dir: PathBuf,
+    /// Opened on the first command
+    #[cfg(unix)]
+    stdin: tokio::sync::Mutex<Option<tokio::net::unix::pipe::Sender>>,
+    #[cfg(not(unix))]
+    child: std::sync::Mutex<tokio::process::Child>,
+    #[cfg(not(unix))]
+    stdin: tokio::sync::Mutex<Option<tokio::process::ChildStdin>>,
+}
+
+impl NativeProcess {
+    /// Start `program` in `working_dir`, its output going to new console logs. On Linux the process
+    /// is placed in `cgroup` before the program starts.
+    pub async fn spawn(
+        program: &str,
+        args: &[String],
+        env: &[(String, String)],
+        working_dir: &Path,
+        cgroup: Option<&Path>,
+    ) -> Result<Self, AppError> {
+        let run_dir = working_dir.join(RUN_DIR);
+        tokio::fs::create_dir_all(&run_dir).await?;
+        for name in [PID_FILE, EXIT_FILE] {
+            let _ = tokio::fs::remove_file(run_dir.join(name)).await;
+        }
+
+        let log_path = logs::rotate_console(working_dir)?;
+
+        #[cfg(unix)]
+        {
+            make_fifo(&run_dir.join(STDIN_FIFO))?;
+
+            let mut wrapper = Command::new("sh")
+                .arg("-c")
+                .arg(WRAPPER_SCRIPT)
+                .arg("draveur-run")
+                .arg(program)
+                .args(args)
+                .env("DRAVEUR_RUN", &run_dir)
+                .env("DRAVEUR_LOG", &log_path)
+                .env("DRAVEUR_ERR_LOG", stderr_log_path(working_dir))
+                .env("DRAVEUR_CGROUP", cgroup.unwrap_or(Path::new("")))
+                .envs(env.iter().map(|(key, value)| (key, value)))
+                .current_dir(working_dir)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                // Out of the panel's process group: signals sent to the panel do not reach it
+                .process_group(0)
+                .spawn()?;
+
+            // Reap the wrapper once it exits; after a panel restart init does it
+            tokio::spawn(async move {
+                let _ = wrapper.wait().await;
+            });
+
+            let deadline = tokio::time::Instant::now() + SPAWN_TIMEOUT;
+            loop {
+                if let Some((pid, wrapper_pid)) = read_pids(&run_dir) {
+                    let wrapper_started = start_time(wrapper_pid);
+                    return Ok(Self { pid, wrapper_pid, wrapper_started, run_dir, stdin: Default::default() });
+                }
+                if tokio::time::Instant::now() >= deadline {
+                    return Err(AppError::Internal("The server wrapper did not report its PID".into()));
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = cgroup;
+            let log_file = std::fs::OpenOptions::new().append(true).open(&log_path)?;
+            let stderr_file = std::fs::OpenOptions::new().append(true).open(stderr_log_path(working_dir))?;
+            let mut child = Command::new(program)
+                .args(args)
+                .envs(env.iter().map(|(key, value)| (key, value)))
+                .current_dir(working_dir)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::from(log_file))
+                .stderr(Stdio::from(stderr_file))
+                .spawn()?;
+            let pid = child.id().unwrap_or_default();
+            let stdin = tokio::sync::Mutex::new(child.stdin.take());
+            Ok(Self { pid, child: std::sync::Mutex::new(child), stdin })
+        }
+    }
+
+    /// Find the game server left running in `working_dir` by a previous panel instance
+    #[cfg(unix)]
+    pub fn attach(working_dir: &Path) -> Option<Self> {
+        let run_dir = working_dir.join(RUN_DIR);
+        if run_dir.join(EXIT_FILE).exists() {
+            return None;
+        }
+        let (pid, wrapper_pid) = read_pids(&run_dir)?;
+
+        // PIDs from before a reboot may since have been reused by unrelated processes
+        let started = std::fs::metadata(run_dir.join(PID_FILE)).ok()?.modified().ok()?;
+        let started = started.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        if started < sysinfo::System::boot_time() || !alive(wrapper_pid) {
+            return None;
+        }
+        let mut system = sysinfo::System::new();
+        let game = sysinfo::Pid::from_u32(pid);
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[game]), true);
+        if system.process(game)?.parent() != Some(sysinfo::Pid::from_u32(wrapper_pid)) {
+            return None;
+        }
+
+        let wrapper_started = start_time(wrapper_pid);
+        Some(Self { pid, wrapper_pid, wrapper_started, run_dir, stdin: Default::default() })
+    }
+
+    /// Child processes cannot be taken over by another panel instance
+    #[cfg(not(unix))]
+    pub fn attach(_working_dir: &Path) -> Option<Self> {
+        None
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// `Some` once the process has exited
+    pub fn try_exit(&self) -> Option<Exit> {
+        #[cfg(unix)]
+        {
+            // The exit file is written last by the wrapper; without it, the wrapper only counts as
+            // running while its PID still belongs to it
+            let exit_file = self.run_dir.join(EXIT_FILE);
+            if !exit_file.exists()
+                && alive(self.wrapper_pid)
+                && self.wrapper_started.is_none_or(|started| start_time(self.wrapper_pid) == Some(started))
+            {
+                return None;
+            }
+            let code = std::fs::read_to_string(exit_file)
+                .ok()
+                .and_then(|c| c.trim().parse().ok());
+            Some(Exit { code })
+        }
+
+        #[cfg(not(unix))]
+        {
+            match self.child.lock().unwrap_or_else(|e| e.into_inner()).try_wait() {
+                Ok(Some(status)) => Some(Exit { code: status.code() }),
+                _ => None,
+            }
+        }
+    }
+
+    /// Write a line to the server's console
+    pub async fn send_line(&self, line: &str) -> std::io::Result<()> {
+        let mut input = self.stdin.lock().await;
+        #[cfg(unix)]
+        let stdin = match input.as_mut() {
+            Some(stdin) => stdin,
+            None => input.insert(
+                tokio::net::unix::pipe::OpenOptions::new().open_sender(self.run_dir.join(STDIN_FIFO))?,
+            ),
+        };
+        #[cfg(not(unix))]
+        let stdin = input.as_mut()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "stdin closed"))?;
+
+        let result = async {
+            stdin.write_all(format!("{line}\n").as_bytes()).await?;
+            stdin.flush().await
+        }.await;
+
+        #[cfg(unix)]
+        if result.is_err() {
+            *input = None;
+        }
+        result
+    }
+
+    pub async fn kill(&self) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            // SAFETY: plain syscall on a PID, no memory involved
+            if unsafe { libc::kill(self.pid as libc::pid_t, libc::SIGKILL) } == 0 {
+                return Ok(());
+            }
+            let error = std::io::Error::last_os_error();
+            if error.raw_os_error() == Some(libc::ESRCH) { Ok(()) } else { Err(error) }
+        }
+
+        #[cfg(not(unix))]
+        {
+            self.child.lock().unwrap_or_else(|e| e.into_inner()).start_kill()
+        }
+    }
+}
+
+/// Follow the console log from byte `offset`, handing every line to `on_line`, until `owner` is
+/// dropped (the process left the manager) and the rest of the file has been read. When the log is
+/// rotated meanwhile, its last lines are read from the rotated log before going on with the new one.
+pub async fn follow_console(path: PathBuf, offset: u64, owner: Weak<()>, mut on_line: impl FnMut(String)) {
+    let mut segment = logs::segment_name(&path);
+    let mut reader = match open_at(&path, offset).await {
+        Ok(reader) => reader,
+        Err(e) => {
+            tracing::warn!("Cannot follow console log {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let mut buffer = Vec::new();
+    loop {
+        // Checked before reading so that everything written before the owner went away is read
+        let finished = owner.strong_count() == 0;
+        match reader.read_until(b'\n', &mut buffer).await {
+            Ok(0) | Err(_) => {
+                if finished {
+                    if !buffer.is_empty() {
+                        on_line(String::from_utf8_lossy(&buffer).trim_end().to_string());
+                    }
+                    return;
+                }
+                // Checked after the wait, right before reading on: a log emptied by a rotation
+                // may have grown past our position meanwhile
+                tokio::time::sleep(TAIL_INTERVAL).await;
+                let current = logs::segment_name(&path);
+                if current == segment {
+                    continue;
+                }
+                // Rotated, either renamed or copied then emptied: the rotated log holds the same
+                // bytes up to where we are, and whatever was written after
+                let position = reader.stream_position().await.unwrap_or(0);
+                if let Ok(mut rotated) = open_at(&path.with_file_name(&segment), position).await {
+                    let _ = rotated.read_to_end(&mut buffer).await;
+                }
+                let rest = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+                if !rest.is_empty() {
+                    for line in rest.split(|&b| b == b'\n') {
+                        on_line(String::from_utf8_lossy(line).trim_end_matches('\r').to_string());
+                    }
+                }
+                buffer.clear();
+                segment = current;
+                reader = loop {
+                    match open_at(&path, 0).await {
+                        Ok(reader) => break reader,
+                        Err(_) if owner.strong_count() == 0 => return,
+                        Err(_) => tokio::time::sleep(TAIL_INTERVAL).await,
+                    }
+                };
+            }
+            // A partial line stays in the buffer until the rest is written
+            Ok(_) if buffer.ends_with(b"\n") => {
+                on_line(String::from_utf8_lossy(&buffer).trim_end_matches(['\r', '\n']).to_string());
+                buffer.clear();
+            }
+            Ok(_) => {}
+        }
+    }
+}
+
+async fn open_at(path: &Path, offset: u64) -> std::io::Result<BufReader<tokio::fs::File>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    Ok(BufReader::new(file))
+}
+
+#[cfg(unix)]
+fn read_pids(run_dir: &Path) -> Option<(u32, u32)> {
+    let content = std::fs::read_to_string(run_dir.join(PID_FILE)).ok()?;
+    let mut pids = content.split_whitespace().map(|p| p.parse::<u32>().ok());
+    Some((pids.next()??, pids.next()??))
+}
+
+/// Start time of a process, in seconds since the epoch
+#[cfg(unix)]
+fn start_time(pid: u32) -> Option<u64> {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid);
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(sysinfo::Process::start_time)
+}
+
+#[cfg(unix)]
+fn alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists
+    let sent = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0;
+    sent || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(unix)]
+fn make_fifo(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_fifo() => return Ok(()),
+        Ok(_) => std::fs::remove_file(path)?,
+        Err(_) => {}
+    }
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: `c_path` is a valid NUL-terminated string for the duration of the call
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
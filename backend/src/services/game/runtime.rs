@@ -0,0 +1,220 @@
+// Managed Java runtimes
+// JDKs the panel installs itself under data/runtimes, from an archive already on the host or
+// downloaded from a mirror, once its SHA-256 matches the one given by the administrator.
+// Servers reference a runtime by id instead of a `java_path`.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use tar::Archive;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::api::system::check_java_version;
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+pub const DEFAULT_RUNTIMES_DIR: &str = "./data/runtimes";
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct JavaRuntime {
+    pub id: String,
+    pub name: String,
+    /// As reported by `java -version`, e.g. "25.0.1"
+    pub version: String,
+    pub major: i64,
+    /// Absolute path of the `java` binary
+    pub java_path: String,
+    /// URL or host path the archive came from
+    pub source: String,
+    pub sha256: String,
+    pub created_at: String,
+}
+
+/// Where the runtimes are installed. Priority: Env > Default
+pub fn runtimes_dir() -> PathBuf {
+    std::env::var("RUNTIMES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_RUNTIMES_DIR))
+}
+
+/// Lowest Java feature release a game runs on, when it is known
+pub fn required_java(game_type: &str) -> Option<u32> {
+    match game_type.to_lowercase().as_str() {
+        "hytale" => Some(25),
+        "minecraft" => Some(21),
+        _ => None,
+    }
+}
+
+pub fn not_found() -> AppError {
+    AppError::NotFound("runtimes.not_found".into()).with_code(ErrorCode::RuntimeNotFound)
+}
+
+fn invalid_archive(message: String) -> AppError {
+    AppError::BadRequest(message).with_code(ErrorCode::RuntimeInvalidArchive)
+}
+
+pub async fn load(pool: &DbPool, id: &str) -> Result<Option<JavaRuntime>, AppError> {
+    Ok(sqlx::query_as("SELECT * FROM java_runtimes WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+}
+
+pub async fn list(pool: &DbPool) -> Result<Vec<JavaRuntime>, AppError> {
+    Ok(sqlx::query_as("SELECT * FROM java_runtimes ORDER BY major DESC, name")
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Install the JDK of a .tar.gz archive, `source` being an http(s) URL or a path on the host
+pub async fn install(pool: &DbPool, name: Option<&str>, source: &str, sha256: &str) -> Result<JavaRuntime, AppError> {
+    let id = Uuid::new_v4().to_string();
+    let dir = runtimes_dir();
+    tokio::fs::create_dir_all(&dir).await?;
+    let staging = dir.join(format!(".{id}"));
+
+    let is_url = source.starts_with("http://") || source.starts_with("https://");
+    let archive = if is_url { dir.join(format!(".{id}.tar.gz")) } else { PathBuf::from(source) };
+
+    let result = async {
+        let digest = if is_url { download(source, &archive).await? } else { hash_file(&archive).await? };
+        if !digest.eq_ignore_ascii_case(sha256.trim()) {
+            return Err(AppError::BadRequest(format!("Checksum mismatch: expected {}, got {digest}", sha256.trim()))
+                .with_code(ErrorCode::RuntimeChecksumMismatch));
+        }
+        extract(&archive, &staging, &dir.join(&id)).await
+    }.await;
+
+    if is_url {
+        let _ = tokio::fs::remove_file(&archive).await;
+    }
+    let (java_path, version) = match result {
+        Ok(installed) => installed,
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
+    };
+
+    let runtime = JavaRuntime {
+        major: crate::services::game::jvm::major_version(&version).map_or(0, i64::from),
+        name: name.map(str::trim).filter(|n| !n.is_empty()).map_or_else(|| format!("Java {version}"), str::to_string),
+        id,
+        version,
+        java_path: java_path.to_string_lossy().into_owned(),
+        source: source.to_string(),
+        sha256: sha256.trim().to_lowercase(),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    let inserted = sqlx::query(
+        "INSERT INTO java_runtimes (id, name, version, major, java_path, source, sha256, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&runtime.id)
+    .bind(&runtime.name)
+    .bind(&runtime.version)
+    .bind(runtime.major)
+    .bind(&runtime.java_path)
+    .bind(&runtime.source)
+    .bind(&runtime.sha256)
+    .bind(&runtime.created_at)
+    .execute(pool)
+    .await;
+    if let Err(e) = inserted {
+        let _ = tokio::fs::remove_dir_all(dir.join(&runtime.id)).await;
+        return Err(e.into());
+    }
+    Ok(runtime)
+}
+
+/// Delete a runtime no server uses anymore
+pub async fn remove(pool: &DbPool, id: &str) -> Result<(), AppError> {
+    load(pool, id).await?.ok_or_else(not_found)?;
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM servers WHERE java_runtime = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    if users > 0 {
+        return Err(AppError::BadRequest("runtimes.in_use".into()).with_code(ErrorCode::RuntimeInUse));
+    }
+
+    sqlx::query("DELETE FROM java_runtimes WHERE id = ?").bind(id).execute(pool).await?;
+    let path = runtimes_dir().join(id);
+    if path.exists() {
+        tokio::fs::remove_dir_all(&path).await?;
+    }
+    Ok(())
+}
+
+/// Download `url` to `path`, returning the hex SHA-256 of the content
+async fn download(url: &str, path: &Path) -> Result<String, AppError> {
+    let failed = |e: reqwest::Error| AppError::BadRequest(format!("Failed to download {url}: {e}"))
+        .with_code(ErrorCode::RuntimeInvalidArchive);
+    let mut response = reqwest::get(url).await.and_then(|r| r.error_for_status()).map_err(failed)?;
+
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    while let Some(chunk) = response.chunk().await.map_err(failed)? {
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+async fn hash_file(path: &Path) -> Result<String, AppError> {
+    if !path.is_file() {
+        return Err(invalid_archive(format!("Archive not found: {}", path.display())));
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+        Ok(hex::encode(hasher.finalize()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Unpack the archive into `staging`, check that it holds a working Java and move it to `target`.
+/// Returns the absolute path of the `java` binary and its version.
+async fn extract(archive: &Path, staging: &Path, target: &Path) -> Result<(PathBuf, String), AppError> {
+    let (archive, staging, target) = (archive.to_path_buf(), staging.to_path_buf(), target.to_path_buf());
+    tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&staging)?;
+        Archive::new(GzDecoder::new(std::fs::File::open(&archive)?))
+            .unpack(&staging)
+            .map_err(|e| invalid_archive(format!("Not a .tar.gz archive: {e}")))?;
+
+        // JDK archives hold a top directory, with the binaries under Contents/Home on macOS
+        let binary = WalkDir::new(&staging)
+            .max_depth(5)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| {
+                (e.file_name() == "java" || e.file_name() == "java.exe")
+                    && e.path().parent().and_then(Path::file_name).is_some_and(|p| p == "bin")
+            })
+            .ok_or_else(|| invalid_archive("The archive does not contain a Java runtime".into()))?;
+        let relative = binary.path().strip_prefix(&staging).unwrap_or(binary.path()).to_path_buf();
+
+        std::fs::rename(&staging, &target)?;
+        let java_path = std::fs::canonicalize(target.join(relative))?;
+        match check_java_version(&java_path) {
+            Some(java) => Ok((java_path, java.version)),
+            None => {
+                let _ = std::fs::remove_dir_all(&target);
+                Err(invalid_archive("The Java runtime of the archive does not run on this host".into()))
+            }
+        }
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
@@ -0,0 +1,141 @@
+// Server lifecycle state
+// Each server has a single state, changed only through the transitions allowed below. Readers
+// get a consistent snapshot without touching the processes lock, or a watch channel to follow it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::debug;
+
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+use super::events::ConsoleEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerState {
+    Installing,
+    Starting,
+    Running,
+    AuthRequired,
+    Stopping,
+    Crashed,
+    Stopped,
+}
+
+impl ServerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerState::Installing => "installing",
+            ServerState::Starting => "starting",
+            ServerState::Running => "running",
+            ServerState::AuthRequired => "auth_required",
+            ServerState::Stopping => "stopping",
+            ServerState::Crashed => "crashed",
+            ServerState::Stopped => "stopped",
+        }
+    }
+
+    /// A process (game server or installer) holds the server
+    pub fn is_active(&self) -> bool {
+        !matches!(self, ServerState::Crashed | ServerState::Stopped)
+    }
+
+    /// Console event announcing this state to log subscribers
+    pub fn event(&self) -> ConsoleEvent {
+        ConsoleEvent::Status(self.as_str().to_string())
+    }
+
+    pub fn can_transition_to(&self, next: ServerState) -> bool {
+        use ServerState::*;
+        match (self, next) {
+            (Stopped | Crashed, Installing | Starting) => true,
+            (Crashed, Stopped) => true,
+            (Installing, AuthRequired | Crashed | Stopped) => true,
+            // Authentication can be requested by the installer as well as by the server
+            (AuthRequired, Installing) => true,
+            (Starting | Running | AuthRequired, Starting | Running | AuthRequired | Stopping | Crashed | Stopped) => true,
+            (Stopping, Stopped) => true,
+            // The process could not be killed
+            (Stopping, Running) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ServerState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StateSnapshot {
+    pub state: ServerState,
+    pub since: DateTime<Utc>,
+    /// The active process is the installer rather than the game server
+    pub installing: bool,
+}
+
+impl StateSnapshot {
+    fn new(state: ServerState, installing: bool) -> Self {
+        Self { state, since: Utc::now(), installing }
+    }
+}
+
+impl Default for StateSnapshot {
+    fn default() -> Self {
+        Self::new(ServerState::Stopped, false)
+    }
+}
+
+/// Current state of every server known to the manager; unknown servers are stopped.
+/// The lock is never held across an await.
+#[derive(Clone, Default)]
+pub struct StateTable {
+    states: Arc<Mutex<HashMap<String, watch::Sender<StateSnapshot>>>>,
+}
+
+impl StateTable {
+    pub fn snapshot(&self, server_id: &str) -> StateSnapshot {
+        let states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        states.get(server_id).map(|tx| tx.borrow().clone()).unwrap_or_default()
+    }
+
+    pub fn subscribe(&self, server_id: &str) -> watch::Receiver<StateSnapshot> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        states
+            .entry(server_id.to_string())
+            .or_insert_with(|| watch::channel(StateSnapshot::default()).0)
+            .subscribe()
+    }
+
+    /// Move a server to `next`. Returns whether the state changed; moving to the current state is a no-op.
+    pub fn transition(&self, server_id: &str, next: ServerState) -> Result<bool, AppError> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = states
+            .entry(server_id.to_string())
+            .or_insert_with(|| watch::channel(StateSnapshot::default()).0);
+
+        let current = tx.borrow().clone();
+        if current.state == next {
+            return Ok(false);
+        }
+        if !current.state.can_transition_to(next) {
+            debug!("Rejected state transition of server {}: {} -> {}", server_id, current.state, next);
+            return Err(AppError::BadRequest(format!("Server cannot go from {} to {}", current.state, next))
+                .with_code(ErrorCode::ServerInvalidTransition));
+        }
+
+        let installing = match next {
+            ServerState::Installing => true,
+            ServerState::AuthRequired => current.installing,
+            _ => false,
+        };
+        tx.send_replace(StateSnapshot::new(next, installing));
+        Ok(true)
+    }
+}
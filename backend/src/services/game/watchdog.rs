@@ -0,0 +1,178 @@
+// Crash supervision for running game servers
+// One supervisor task per started process; it restarts servers that exit unexpectedly
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+use super::events::ConsoleEvent;
+use super::manager::ProcessManager;
+use crate::api::servers::models::ServerRow;
+use crate::core::database::DbPool;
+use crate::services::system::discord;
+
+/// Number of console lines kept in memory and stored with a crash record
+pub const LOG_TAIL_LINES: usize = 50;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const BACKOFF_BASE_SECS: u64 = 5;
+const BACKOFF_MAX_SECS: u64 = 300;
+const MAX_RESTARTS_PER_WINDOW: usize = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(600);
+
+/// Outcome of polling a supervised process
+pub enum ProcessExit {
+    Running,
+    /// Stopped or killed through the manager, or replaced by a newer run
+    Gone,
+    Exited {
+        exit_code: Option<i32>,
+        success: bool,
+        log_tail: Vec<String>,
+        log_tx: broadcast::Sender<ConsoleEvent>,
+    },
+}
+
+/// Recent crash timestamps per server, used for backoff and the restart budget
+#[derive(Clone, Default)]
+pub struct CrashTracker {
+    crashes: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl CrashTracker {
+    /// Record a crash and return how many crashes happened within the window, this one included
+    pub fn record(&self, server_id: &str) -> usize {
+        let Ok(mut crashes) = self.crashes.lock() else { return 1 };
+        let now = Instant::now();
+        let history = crashes.entry(server_id.to_string()).or_default();
+        while history.front().is_some_and(|t| now.duration_since(*t) > RESTART_WINDOW) {
+            history.pop_front();
+        }
+        history.push_back(now);
+        history.len()
+    }
+
+    pub fn clear(&self, server_id: &str) {
+        if let Ok(mut crashes) = self.crashes.lock() {
+            crashes.remove(server_id);
+        }
+    }
+}
+
+/// Exponential backoff: 5s, 10s, 20s... capped at 5 minutes
+fn backoff_delay(crash_count: usize) -> Duration {
+    let exponent = crash_count.saturating_sub(1).min(16) as u32;
+    Duration::from_secs((BACKOFF_BASE_SECS << exponent).min(BACKOFF_MAX_SECS))
+}
+
+pub fn spawn_supervisor(pm: ProcessManager, server_id: String, run_id: u64) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            match pm.poll_exit(&server_id, run_id).await {
+                ProcessExit::Running => continue,
+                ProcessExit::Gone => return,
+                ProcessExit::Exited { exit_code, success, log_tail, log_tx } => {
+                    // A clean exit (e.g. "stop" typed in the console) is not a crash
+                    if success {
+                        info!("Server {} exited cleanly", server_id);
+                        return;
+                    }
+                    handle_crash(&pm, &server_id, exit_code, log_tail, log_tx).await;
+                    return;
+                }
+            }
+        }
+    });
+}
+
+async fn handle_crash(
+    pm: &ProcessManager,
+    server_id: &str,
+    exit_code: Option<i32>,
+    log_tail: Vec<String>,
+    log_tx: broadcast::Sender<ConsoleEvent>,
+) {
+    warn!("Server {} crashed (exit code: {:?})", server_id, exit_code);
+
+    let Some(pool) = pm.pool() else { return };
+
+    let crash_count = pm.crash_tracker().record(server_id);
+    let server = load_server(pool, server_id).await;
+    let watchdog_enabled = server.as_ref().is_some_and(|s| s.watchdog_enabled != 0);
+    let will_restart = watchdog_enabled && crash_count <= MAX_RESTARTS_PER_WINDOW;
+
+    let _ = sqlx::query(
+        "INSERT INTO server_crashes (id, server_id, exit_code, log_tail, restarted, crashed_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(server_id)
+    .bind(exit_code)
+    .bind(log_tail.join("\n"))
+    .bind(will_restart as i32)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(pool)
+    .await;
+
+    if let Some(srv) = &server {
+        if let Some(url) = srv.discord_webhook_url.clone().filter(|u| !u.is_empty()) {
+            let description = match exit_code {
+                Some(code) => format!("Le serveur **{}** s'est arrêté de manière inattendue (code {code}).", srv.name),
+                None => format!("Le serveur **{}** s'est arrêté de manière inattendue.", srv.name),
+            };
+            let pool = pool.clone();
+            let name = srv.name.clone();
+            tokio::spawn(async move {
+                discord::send_notification(&pool, "💥 Crash du Serveur", &description, discord::COLOR_ERROR, Some(&name), Some(&url)).await;
+            });
+        }
+    }
+
+    if !watchdog_enabled {
+        return;
+    }
+    if !will_restart {
+        error!(
+            "Watchdog: server {} crashed {} times in {} minutes, giving up",
+            server_id, crash_count, RESTART_WINDOW.as_secs() / 60
+        );
+        let _ = log_tx.send(ConsoleEvent::Notice {
+            source: "watchdog",
+            message: "Too many crashes, automatic restart disabled".to_string(),
+        });
+        return;
+    }
+
+    let delay = backoff_delay(crash_count);
+    info!("Watchdog: restarting server {} in {}s (attempt {})", server_id, delay.as_secs(), crash_count);
+    let _ = log_tx.send(ConsoleEvent::Notice {
+        source: "watchdog",
+        message: format!("Restarting in {}s (attempt {crash_count}/{MAX_RESTARTS_PER_WINDOW})", delay.as_secs()),
+    });
+    drop(log_tx);
+
+    tokio::time::sleep(delay).await;
+
+    // The server may have been started manually, or the watchdog disabled, while we waited
+    if pm.is_running(server_id) || pm.is_installing(server_id) {
+        return;
+    }
+    let Some(srv) = load_server(pool, server_id).await.filter(|s| s.watchdog_enabled != 0) else { return };
+
+    if let Err(e) = crate::api::servers::endpoints::lifecycle::launch_server(pool, pm, &srv).await {
+        error!("Watchdog: failed to restart server {}: {}", server_id, e);
+    }
+}
+
+async fn load_server(pool: &DbPool, server_id: &str) -> Option<ServerRow> {
+    sqlx::query_as("SELECT * FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+}
@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{info, warn, error};
+
+use crate::api::servers::endpoints::lifecycle::launch_server;
+use crate::api::servers::models::ServerRow;
+use crate::core::database::DbPool;
+use crate::services::game::manager::ProcessManager;
+
+/// Start every `auto_start` server once the panel is up, dependencies first
+pub fn start(pool: DbPool, pm: ProcessManager) {
+    tokio::spawn(async move {
+        if let Err(e) = run(&pool, &pm).await {
+            error!("Error in auto-start: {e}");
+        }
+    });
+}
+
+async fn run(pool: &DbPool, pm: &ProcessManager) -> anyhow::Result<()> {
+    let servers: Vec<ServerRow> = sqlx::query_as(
+        "SELECT * FROM servers WHERE auto_start = 1 ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if servers.is_empty() {
+        return Ok(());
+    }
+
+    let settings_rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+        .fetch_all(pool)
+        .await?;
+    let settings_map: HashMap<String, String> = settings_rows.into_iter().collect();
+    let delay = Duration::from_secs(crate::api::settings::autostart_delay_seconds(&settings_map));
+
+    info!("Auto-starting {} server(s) with a {}s stagger", servers.len(), delay.as_secs());
+
+    let mut failed: HashSet<String> = HashSet::new();
+    let mut first = true;
+
+    for server in boot_order(servers) {
+        if let Some(dep) = server.dependencies().into_iter().find(|d| failed.contains(d)) {
+            warn!("Auto-start: skipping server {} because dependency {} did not start", server.name, dep);
+            failed.insert(server.id.clone());
+            continue;
+        }
+
+        if !first {
+            tokio::time::sleep(delay).await;
+        }
+        first = false;
+
+        if pm.is_running(&server.id) {
+            continue;
+        }
+
+        match launch_server(pool, pm, &server).await {
+            Ok(()) => info!("Auto-started server {}", server.name),
+            Err(e) => {
+                error!("Auto-start: failed to start server {}: {}", server.name, e);
+                failed.insert(server.id.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Order servers so that each one comes after the auto-start servers it depends on.
+/// Dependencies outside the auto-start set are ignored; cycles fall back to name order.
+fn boot_order(servers: Vec<ServerRow>) -> Vec<ServerRow> {
+    let ids: HashSet<String> = servers.iter().map(|s| s.id.clone()).collect();
+    let mut pending = servers;
+    let mut ordered: Vec<ServerRow> = Vec::with_capacity(pending.len());
+    let mut started: HashSet<String> = HashSet::new();
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|s| {
+            s.dependencies().iter().all(|d| !ids.contains(d) || started.contains(d))
+        });
+
+        let next = match ready {
+            Some(index) => pending.remove(index),
+            None => {
+                warn!("Auto-start: dependency cycle detected, starting remaining servers in name order");
+                pending.remove(0)
+            }
+        };
+
+        started.insert(next.id.clone());
+        ordered.push(next);
+    }
+
+    ordered
+}
@@ -1,13 +1,407 @@
 use std::fs::File;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
 use flate2::write::GzEncoder;
 use flate2::read::GzDecoder;
 use flate2::Compression;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use sha2::{Digest, Sha256};
 use tar::Archive;
+use tracing::{info, warn};
+use walkdir::WalkDir;
+use crate::core::database::DbPool;
 use crate::core::error::AppError;
 use crate::core::error::codes::ErrorCode;
+use crate::services::game::process::RUN_DIR;
 
-pub async fn create_archive(source_dir: String, backup_file_path: String) -> Result<u64, AppError> {
+use super::chunk_store;
+
+/// Used when neither the BACKUPS_DIR variable nor the `backups_dir` setting is set
+pub const DEFAULT_BACKUPS_DIR: &str = "./data/backups";
+/// Where backups were written before the `backups_dir` setting was honoured
+const LEGACY_BACKUPS_DIR: &str = "backups";
+
+static BACKUPS_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Directory holding archives, manifests and the chunk store
+pub fn backups_dir() -> PathBuf {
+    BACKUPS_ROOT.read().ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_BACKUPS_DIR))
+}
+
+/// Resolve the backups directory from the settings (read once at startup)
+/// and move backups left in the legacy location into it.
+pub async fn init_backups_dir(pool: &DbPool) -> Result<(), AppError> {
+    let settings_rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+        .fetch_all(pool)
+        .await?;
+    let settings_map = settings_rows.into_iter().collect();
+    let dir = PathBuf::from(crate::api::settings::backups_dir(&settings_map));
+
+    std::fs::create_dir_all(&dir)?;
+    if let Ok(mut root) = BACKUPS_ROOT.write() {
+        *root = Some(dir.clone());
+    }
+
+    let legacy = Path::new(LEGACY_BACKUPS_DIR);
+    let same_dir = match (legacy.canonicalize(), dir.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => true,
+    };
+    if !legacy.is_dir() || same_dir {
+        return Ok(());
+    }
+
+    let mut moved = 0;
+    for entry in std::fs::read_dir(legacy)? {
+        let entry = entry?;
+        let target = dir.join(entry.file_name());
+        if target.exists() {
+            warn!("Legacy backup {} already exists in {}, left in place", entry.path().display(), dir.display());
+            continue;
+        }
+        match std::fs::rename(entry.path(), &target) {
+            Ok(()) => moved += 1,
+            Err(e) => warn!("Failed to move legacy backup {}: {}", entry.path().display(), e),
+        }
+    }
+    let _ = std::fs::remove_dir(legacy);
+    if moved > 0 {
+        info!("Moved {} legacy backup entries into {}", moved, dir.display());
+    }
+    Ok(())
+}
+
+/// Full tar.gz of the working directory
+pub const KIND_ARCHIVE: &str = "archive";
+/// Manifest over the deduplicated chunk store
+pub const KIND_INCREMENTAL: &str = "incremental";
+
+/// Created from the API
+pub const ORIGIN_MANUAL: &str = "manual";
+/// Created by a backup policy or a scheduled task; the only backups retention prunes
+pub const ORIGIN_SCHEDULED: &str = "scheduled";
+/// Uploaded archive
+pub const ORIGIN_IMPORT: &str = "import";
+/// Fetched back from a backup destination
+pub const ORIGIN_REPLICA: &str = "replica";
+/// Snapshot taken before a restore, to roll it back
+pub const ORIGIN_PRE_RESTORE: &str = "pre_restore";
+
+pub fn is_valid_kind(kind: &str) -> bool {
+    kind == KIND_ARCHIVE || kind == KIND_INCREMENTAL
+}
+
+/// Hytale defaults: logs, lock files, downloaded assets/binaries and the server jar directory
+pub const DEFAULT_EXCLUDES: &[&str] = &["logs/", "*.lck", "*.zip", "Server/", "hytale-downloader-*"];
+
+/// Per-server include/exclude glob rules, matched against '/'-separated paths relative to the working directory.
+/// A pattern ending with '/' (or '/**') matches a directory and everything below it.
+pub struct BackupFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl BackupFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, AppError> {
+        let include = if include.is_empty() { None } else { Some(build_globset(include)?) };
+        Ok(Self { include, exclude: build_globset(exclude)? })
+    }
+
+    /// Build the filter from the JSON columns of a server row; NULL means "use the defaults"
+    pub fn from_columns(include: Option<&str>, exclude: Option<&str>) -> Result<Self, AppError> {
+        let (include, exclude) = rules_from_columns(include, exclude);
+        Self::new(&include, &exclude)
+    }
+
+    pub fn allows_dir(&self, relative: &str) -> bool {
+        !self.exclude.is_match(relative)
+    }
+
+    pub fn allows_file(&self, relative: &str) -> bool {
+        !self.exclude.is_match(relative) && self.include.as_ref().is_none_or(|i| i.is_match(relative))
+    }
+}
+
+/// Effective include/exclude lists for the JSON columns of a server row
+pub fn rules_from_columns(include: Option<&str>, exclude: Option<&str>) -> (Vec<String>, Vec<String>) {
+    let include = include.and_then(|i| serde_json::from_str(i).ok()).unwrap_or_default();
+    let exclude = exclude.and_then(|e| serde_json::from_str(e).ok())
+        .unwrap_or_else(|| DEFAULT_EXCLUDES.iter().map(|p| p.to_string()).collect());
+    (include, exclude)
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet, AppError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim().trim_start_matches("./");
+        let base = pattern.trim_end_matches("/**").trim_end_matches('/');
+        if base.is_empty() {
+            continue;
+        }
+
+        let mut add = |p: &str| -> Result<(), AppError> {
+            let glob = Glob::new(p).map_err(|e| {
+                AppError::BadRequest(format!("Invalid backup rule '{pattern}': {e}"))
+                    .with_code(ErrorCode::BackupInvalidRule)
+            })?;
+            builder.add(glob);
+            Ok(())
+        };
+
+        add(base)?;
+        if base != pattern {
+            add(&format!("{base}/**"))?;
+        }
+    }
+    builder.build().map_err(|e| AppError::Internal(format!("Failed to build backup rules: {e}")))
+}
+
+/// Walk `source` and yield the entries allowed by `filter`, with their relative path.
+/// Excluded directories are not descended into.
+pub fn filtered_entries<'a>(
+    source: &'a Path,
+    filter: &'a BackupFilter,
+) -> impl Iterator<Item = Result<(walkdir::DirEntry, String), AppError>> + 'a {
+    WalkDir::new(source)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(move |entry| {
+            // The panel's run files (console FIFO, PIDs) are never part of a backup
+            if entry.depth() == 1 && entry.file_name() == RUN_DIR {
+                return false;
+            }
+            !entry.file_type().is_dir() || relative_path(source, entry.path()).is_ok_and(|r| filter.allows_dir(&r))
+        })
+        .filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(AppError::Internal(format!("Failed to walk directory: {e}")))),
+            };
+            let relative = match relative_path(source, entry.path()) {
+                Ok(r) => r,
+                Err(e) => return Some(Err(e)),
+            };
+            let allowed = if entry.file_type().is_dir() {
+                filter.include.as_ref().is_none_or(|i| i.is_match(&relative))
+            } else {
+                filter.allows_file(&relative)
+            };
+            allowed.then_some(Ok((entry, relative)))
+        })
+}
+
+/// Reject relative paths that would escape their root (absolute paths, "..")
+pub fn safe_relative(path: &str) -> Result<PathBuf, AppError> {
+    let relative = PathBuf::from(path);
+    if !path.is_empty() && relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        Ok(relative)
+    } else {
+        Err(AppError::BadRequest(format!("Invalid backup path: {path}"))
+            .with_code(ErrorCode::BackupRestoreFailed))
+    }
+}
+
+/// Whether the relative `path` is `root` itself or lies below it
+pub fn is_within(path: &str, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    path == root || path.strip_prefix(root).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Relative paths always use '/' so rules and manifests behave the same on every platform
+pub fn relative_path(root: &Path, path: &Path) -> Result<String, AppError> {
+    let relative = path.strip_prefix(root)
+        .map_err(|_| AppError::Internal(format!("Path outside of backup root: {}", path.display())))?;
+    Ok(relative.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Modification time in seconds since the epoch, as stored in tar headers
+pub fn file_mtime(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata.modified().ok()?
+        .duration_since(std::time::UNIX_EPOCH).ok()
+        .map(|d| d.as_secs())
+}
+
+/// File name of a new backup. The prefix is user-provided and ends up in a path.
+pub fn backup_filename(prefix: &str, server_id: &str, kind: &str) -> String {
+    let prefix: String = prefix.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_').collect();
+    let prefix = if prefix.is_empty() { "backup" } else { prefix.as_str() };
+    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+    let extension = if kind == KIND_INCREMENTAL { chunk_store::MANIFEST_SUFFIX } else { ".tar.gz" };
+    format!("{prefix}_{server_id}_{timestamp}{extension}")
+}
+
+/// A backup written to the backups directory
+pub struct CreatedBackup {
+    pub filename: String,
+    /// Archive size, or the total size of the files for incremental backups
+    pub size: u64,
+    /// SHA-256 of the archive or manifest file
+    pub checksum: String,
+}
+
+/// Back up `source_dir` as `kind`
+pub async fn create_backup(
+    server_id: &str,
+    source_dir: String,
+    prefix: &str,
+    kind: &str,
+    filter: BackupFilter,
+) -> Result<CreatedBackup, AppError> {
+    let filename = backup_filename(prefix, server_id, kind);
+    let path = backups_dir().join(&filename);
+    let path_str = path.to_string_lossy().to_string();
+
+    let size = if kind == KIND_INCREMENTAL {
+        chunk_store::create_snapshot(source_dir, path_str, filter).await?
+    } else {
+        create_archive(source_dir, path_str, filter).await?
+    };
+    let checksum = file_checksum(path).await?;
+
+    Ok(CreatedBackup { filename, size, checksum })
+}
+
+/// Hex SHA-256 of a file
+pub async fn file_checksum(path: PathBuf) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut File::open(&path)?, &mut hasher)?;
+        Ok(hex::encode(hasher.finalize()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Re-read a backup completely: every archive entry, or every chunk of a manifest.
+/// Returns the checksum of the backup file; an error describes the corruption.
+pub async fn verify_backup(filename: &str, kind: &str) -> Result<String, AppError> {
+    let path = backups_dir().join(safe_relative(filename)?);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Backup file not found: {filename}"))
+            .with_code(ErrorCode::BackupNotFound));
+    }
+
+    if kind == KIND_INCREMENTAL {
+        chunk_store::verify_snapshot(path.clone()).await?;
+        file_checksum(path).await
+    } else {
+        verify_archive(path, false).await
+    }
+}
+
+/// Check an uploaded `.tar.gz` before it is registered as a backup: it must read back
+/// completely and only contain regular files, directories and links below its root.
+/// Returns its checksum.
+pub async fn validate_import(path: PathBuf) -> Result<String, AppError> {
+    verify_archive(path, true).await
+}
+
+/// Decompress the whole archive and read every entry, hashing the file on the way.
+/// With `check_entries` the archive comes from a client, so problems are reported as a bad request.
+async fn verify_archive(path: PathBuf, check_entries: bool) -> Result<String, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let corrupt = |e: std::io::Error| if check_entries {
+            AppError::BadRequest(format!("Invalid archive: {e}")).with_code(ErrorCode::BackupInvalidArchive)
+        } else {
+            AppError::Internal(format!("Corrupted archive: {e}")).with_code(ErrorCode::BackupCorrupted)
+        };
+
+        let reader = HashingReader { inner: File::open(&path)?, hasher: Sha256::new() };
+        let mut archive = Archive::new(GzDecoder::new(reader));
+        let mut entries = 0;
+        for entry in archive.entries().map_err(corrupt)? {
+            let mut entry = entry.map_err(corrupt)?;
+            if check_entries {
+                check_archive_entry(&entry)?;
+            }
+            std::io::copy(&mut entry, &mut std::io::sink()).map_err(corrupt)?;
+            entries += 1;
+        }
+        if check_entries && entries == 0 {
+            return Err(AppError::BadRequest("Archive is empty".into())
+                .with_code(ErrorCode::BackupInvalidArchive));
+        }
+
+        // Drain the end-of-archive blocks so the gzip trailer (CRC and length) is checked too
+        let mut decoder = archive.into_inner();
+        std::io::copy(&mut decoder, &mut std::io::sink()).map_err(corrupt)?;
+        let mut reader = decoder.into_inner();
+        std::io::copy(&mut reader, &mut std::io::sink())?;
+
+        Ok(hex::encode(reader.hasher.finalize()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+fn check_archive_entry<R: Read>(entry: &tar::Entry<R>) -> Result<(), AppError> {
+    let invalid = |reason: String| AppError::BadRequest(reason).with_code(ErrorCode::BackupInvalidArchive);
+
+    let path = entry.path().map_err(|e| invalid(format!("Unreadable entry path: {e}")))?;
+    let path = path.to_string_lossy();
+    // Archives made with `tar -C dir .` store paths as "./..."
+    let relative = path.trim_start_matches("./").trim_end_matches('/');
+    if !relative.is_empty() && relative != "." {
+        safe_relative(relative).map_err(|_| invalid(format!("Unsafe path in archive: {path}")))?;
+    }
+
+    let kind = entry.header().entry_type();
+    if !(kind.is_file() || kind.is_dir() || kind.is_symlink() || kind.is_hard_link() || kind.is_pax_global_extensions()) {
+        return Err(invalid(format!("Unsupported entry type in archive: {path}")));
+    }
+    Ok(())
+}
+
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// Extract a backup into `dest_dir`; with `only`, just that relative path
+pub async fn extract_backup(filename: &str, kind: &str, dest_dir: String, only: Option<String>) -> Result<(), AppError> {
+    let path = backups_dir().join(filename).to_string_lossy().to_string();
+
+    if kind == KIND_INCREMENTAL {
+        chunk_store::restore_snapshot(path, dest_dir, only).await
+    } else {
+        extract_archive(path, dest_dir, only).await
+    }
+}
+
+/// Remove a backup file from disk. Chunks are only released by `chunk_store::collect_garbage`.
+async fn remove_backup_file(filename: &str) -> Result<(), AppError> {
+    let file_path = backups_dir().join(filename);
+    if file_path.exists() {
+        tokio::fs::remove_file(file_path).await?;
+    }
+    Ok(())
+}
+
+/// Remove a backup from disk, releasing the chunks only it referenced
+pub async fn delete_backup_files(filename: &str, kind: &str) -> Result<(), AppError> {
+    remove_backup_file(filename).await?;
+    if kind == KIND_INCREMENTAL {
+        chunk_store::collect_garbage().await?;
+    }
+    Ok(())
+}
+
+pub async fn create_archive(source_dir: String, backup_file_path: String, filter: BackupFilter) -> Result<u64, AppError> {
     tokio::task::spawn_blocking(move || {
         let source_path = Path::new(&source_dir);
         let backup_path = Path::new(&backup_file_path);
@@ -25,7 +419,14 @@ pub async fn create_archive(source_dir: String, backup_file_path: String) -> Res
         let enc = GzEncoder::new(tar_gz, Compression::default());
         let mut tar = tar::Builder::new(enc);
 
-        tar.append_dir_all(".", source_path)?;
+        for entry in filtered_entries(source_path, &filter) {
+            let (entry, relative) = entry?;
+            if entry.file_type().is_dir() {
+                tar.append_dir(&relative, entry.path())?;
+            } else {
+                tar.append_path_with_name(entry.path(), &relative)?;
+            }
+        }
         tar.finish()?;
 
         let metadata = std::fs::metadata(backup_path)?;
@@ -35,7 +436,7 @@ pub async fn create_archive(source_dir: String, backup_file_path: String) -> Res
     .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
 }
 
-pub async fn extract_archive(backup_file_path: String, dest_dir: String) -> Result<(), AppError> {
+pub async fn extract_archive(backup_file_path: String, dest_dir: String, only: Option<String>) -> Result<(), AppError> {
     tokio::task::spawn_blocking(move || {
         let backup_path = Path::new(&backup_file_path);
         let dest_path = Path::new(&dest_dir);
@@ -53,9 +454,53 @@ pub async fn extract_archive(backup_file_path: String, dest_dir: String) -> Resu
         let tar = GzDecoder::new(tar_gz);
         let mut archive = Archive::new(tar);
 
-        archive.unpack(dest_path)?;
+        let Some(only) = only else {
+            archive.unpack(dest_path)?;
+            return Ok(());
+        };
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            // Older archives store paths as "./..."
+            let entry_path = entry.path()?.to_string_lossy().trim_start_matches("./").to_string();
+            if is_within(&entry_path, &only) {
+                entry.unpack_in(dest_path)?;
+            }
+        }
         Ok(())
     })
     .await
     .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
 }
+
+/// Delete the oldest scheduled backups of a server beyond `max_backups`, from disk and from the
+/// table. Backups made by users, imported or taken before a restore are left alone.
+/// Returns the number of pruned backups.
+pub async fn prune_backups(pool: &DbPool, server_id: &str, max_backups: usize) -> Result<usize, AppError> {
+    let backups: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT id, filename, kind FROM backups WHERE server_id = ? AND origin = ? ORDER BY created_at DESC"
+    )
+    .bind(server_id)
+    .bind(ORIGIN_SCHEDULED)
+    .fetch_all(pool)
+    .await?;
+
+    let mut pruned = 0;
+    let mut release_chunks = false;
+    for (id, filename, kind) in backups.into_iter().skip(max_backups) {
+        remove_backup_file(&filename).await?;
+        sqlx::query("DELETE FROM backups WHERE id = ?")
+            .bind(&id)
+            .execute(pool)
+            .await?;
+        release_chunks |= kind == KIND_INCREMENTAL;
+        pruned += 1;
+    }
+
+    // One collection for the whole batch
+    if release_chunks {
+        chunk_store::collect_garbage().await?;
+    }
+
+    Ok(pruned)
+}
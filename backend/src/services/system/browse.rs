@@ -0,0 +1,240 @@
+// Backup browsing
+// Lists the files inside a backup (tar.gz archive or incremental manifest) or a live working
+// directory in the same shape, so any two of them can be compared before a restore.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use futures::Stream;
+use serde::Serialize;
+use tar::Archive;
+use tokio::sync::{mpsc, oneshot};
+
+use super::backup::{backups_dir, file_mtime, filtered_entries, safe_relative, BackupFilter, KIND_INCREMENTAL};
+use super::chunk_store;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+/// Size of the pieces a file is streamed in
+const STREAM_BUFFER: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupEntry {
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since the epoch; unknown for files of older incremental backups
+    pub mtime: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModifiedEntry {
+    pub path: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    pub old_mtime: Option<u64>,
+    pub new_mtime: Option<u64>,
+}
+
+/// Files only: directories come and go with their content
+#[derive(Debug, Default, Serialize)]
+pub struct BackupDiff {
+    pub added: Vec<BackupEntry>,
+    pub removed: Vec<BackupEntry>,
+    pub modified: Vec<ModifiedEntry>,
+    pub unchanged: usize,
+}
+
+/// Every entry of a backup
+pub async fn list_backup(filename: &str, kind: &str) -> Result<Vec<BackupEntry>, AppError> {
+    let path = backups_dir().join(safe_relative(filename)?);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Backup file not found: {filename}"))
+            .with_code(ErrorCode::BackupNotFound));
+    }
+    let incremental = kind == KIND_INCREMENTAL;
+
+    tokio::task::spawn_blocking(move || {
+        if incremental {
+            let manifest = chunk_store::read_manifest(&path)?;
+            let dirs = manifest.dirs.into_iter().map(|path| BackupEntry { path, is_dir: true, size: 0, mtime: None });
+            let files = manifest.files.into_iter().map(|f| BackupEntry { path: f.path, is_dir: false, size: f.size, mtime: f.mtime });
+            return Ok(dirs.chain(files).collect());
+        }
+
+        let mut archive = Archive::new(GzDecoder::new(File::open(&path)?));
+        let mut entries = Vec::new();
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let header = entry.header();
+            let kind = header.entry_type();
+            if !kind.is_file() && !kind.is_dir() {
+                continue;
+            }
+            let Some(path) = entry_path(&entry) else { continue };
+            entries.push(BackupEntry {
+                path,
+                is_dir: kind.is_dir(),
+                size: if kind.is_dir() { 0 } else { header.size()? },
+                mtime: header.mtime().ok(),
+            });
+        }
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Entries of a working directory, limited to what a backup with `filter` would contain
+pub async fn list_live(working_dir: String, filter: BackupFilter) -> Result<Vec<BackupEntry>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let root = Path::new(&working_dir);
+        if !root.exists() {
+            return Err(AppError::NotFound(format!("Source directory not found: {working_dir}"))
+                .with_code(ErrorCode::ServerDirMissing));
+        }
+
+        let mut entries = Vec::new();
+        for entry in filtered_entries(root, &filter) {
+            let (entry, path) = entry?;
+            let file_type = entry.file_type();
+            if !file_type.is_file() && !file_type.is_dir() {
+                continue;
+            }
+            let metadata = entry.metadata()
+                .map_err(|e| AppError::Internal(format!("Failed to read {path}: {e}")))?;
+            entries.push(BackupEntry {
+                path,
+                is_dir: file_type.is_dir(),
+                size: if file_type.is_dir() { 0 } else { metadata.len() },
+                mtime: file_mtime(&metadata),
+            });
+        }
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Compare two listings by path. A file is modified when its size changed, or its
+/// modification time when both sides know it.
+pub fn diff(old: Vec<BackupEntry>, new: Vec<BackupEntry>) -> BackupDiff {
+    let mut old_files: HashMap<String, BackupEntry> = old.into_iter()
+        .filter(|e| !e.is_dir)
+        .map(|e| (e.path.clone(), e))
+        .collect();
+
+    let mut diff = BackupDiff::default();
+    for entry in new.into_iter().filter(|e| !e.is_dir) {
+        let Some(previous) = old_files.remove(&entry.path) else {
+            diff.added.push(entry);
+            continue;
+        };
+        let mtime_changed = matches!((previous.mtime, entry.mtime), (Some(a), Some(b)) if a != b);
+        if previous.size != entry.size || mtime_changed {
+            diff.modified.push(ModifiedEntry {
+                path: entry.path,
+                old_size: previous.size,
+                new_size: entry.size,
+                old_mtime: previous.mtime,
+                new_mtime: entry.mtime,
+            });
+        } else {
+            diff.unchanged += 1;
+        }
+    }
+    diff.removed = old_files.into_values().collect();
+
+    diff.added.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.removed.sort_by(|a, b| a.path.cmp(&b.path));
+    diff.modified.sort_by(|a, b| a.path.cmp(&b.path));
+    diff
+}
+
+type FileStream = Box<dyn Stream<Item = std::io::Result<Vec<u8>>> + Send + Unpin>;
+
+/// Stream one file out of a backup without extracting the rest. Returns its size and content.
+pub async fn read_file(filename: &str, kind: &str, file: &str) -> Result<(u64, FileStream), AppError> {
+    let path = backups_dir().join(safe_relative(filename)?);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("Backup file not found: {filename}"))
+            .with_code(ErrorCode::BackupNotFound));
+    }
+    let wanted = safe_relative(file.trim_matches('/'))?.to_string_lossy().replace('\\', "/");
+    let incremental = kind == KIND_INCREMENTAL;
+
+    let (size_tx, size_rx) = oneshot::channel::<Result<u64, AppError>>();
+    let (data_tx, data_rx) = mpsc::channel::<std::io::Result<Vec<u8>>>(8);
+
+    tokio::task::spawn_blocking(move || {
+        let mut size_tx = Some(size_tx);
+        let result = with_entry(&path, &wanted, incremental, |size, reader| {
+            if let Some(tx) = size_tx.take() {
+                let _ = tx.send(Ok(size));
+            }
+            send_content(reader, &data_tx);
+        });
+        if let (Err(e), Some(tx)) = (result, size_tx) {
+            let _ = tx.send(Err(e));
+        }
+    });
+
+    let size = size_rx.await
+        .map_err(|_| AppError::Internal("Backup reader stopped unexpectedly".into()))??;
+
+    let stream = futures::stream::unfold(data_rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    });
+    Ok((size, Box::new(Box::pin(stream))))
+}
+
+/// Find the file `wanted` in a backup and hand its size and content to `found`
+fn with_entry(path: &Path, wanted: &str, incremental: bool, found: impl FnOnce(u64, &mut dyn Read)) -> Result<(), AppError> {
+    let not_found = || AppError::NotFound(format!("Path not found in backup: {wanted}"))
+        .with_code(ErrorCode::BackupPathNotFound);
+
+    if incremental {
+        let manifest = chunk_store::read_manifest(path)?;
+        let file = manifest.files.iter().find(|f| f.path == wanted).ok_or_else(not_found)?;
+        found(file.size, &mut chunk_store::file_reader(file));
+        return Ok(());
+    }
+
+    let mut archive = Archive::new(GzDecoder::new(File::open(path)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file() && entry_path(&entry).as_deref() == Some(wanted) {
+            found(entry.size(), &mut entry);
+            return Ok(());
+        }
+    }
+    Err(not_found())
+}
+
+fn send_content(reader: &mut dyn Read, tx: &mpsc::Sender<std::io::Result<Vec<u8>>>) {
+    let mut buffer = vec![0u8; STREAM_BUFFER];
+    loop {
+        let item = match reader.read(&mut buffer) {
+            Ok(0) => return,
+            Ok(read) => Ok(buffer[..read].to_vec()),
+            Err(e) => Err(e),
+        };
+        let failed = item.is_err();
+        // The client went away
+        if tx.blocking_send(item).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// Relative path of an archive entry; `None` for the archive root.
+/// Older archives store paths as "./...".
+fn entry_path<R: Read>(entry: &tar::Entry<R>) -> Option<String> {
+    let path = entry.path().ok()?;
+    let path = path.to_string_lossy();
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    (!path.is_empty() && path != ".").then(|| path.to_string())
+}
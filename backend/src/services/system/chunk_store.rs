@@ -0,0 +1,369 @@
+// Content-addressed chunk store for incremental backups
+// Files are split into fixed-size chunks stored once under `backups/chunks/<aa>/<sha256>`;
+// each backup is a JSON manifest listing the chunks of every file.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use super::backup::{backups_dir, file_mtime, filtered_entries, is_within, safe_relative, BackupFilter};
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+pub const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// Serializes snapshot creation and garbage collection, so a collection never
+/// deletes chunks written by a snapshot whose manifest is not saved yet
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub dirs: Vec<String>,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    #[serde(default)]
+    pub mode: Option<u32>,
+    /// Modification time in seconds since the epoch; missing from older manifests
+    #[serde(default)]
+    pub mtime: Option<u64>,
+    pub chunks: Vec<String>,
+}
+
+fn chunks_dir() -> PathBuf {
+    backups_dir().join("chunks")
+}
+
+fn chunk_path(hash: &str) -> PathBuf {
+    chunks_dir().join(&hash[..2]).join(hash)
+}
+
+/// Snapshot `source_dir` into the chunk store and write its manifest. Returns the total size of the files.
+pub async fn create_snapshot(source_dir: String, manifest_path: String, filter: BackupFilter) -> Result<u64, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let source_path = Path::new(&source_dir);
+        if !source_path.exists() {
+            return Err(AppError::NotFound(format!("Source directory not found: {source_dir}"))
+                .with_code(ErrorCode::ServerDirMissing));
+        }
+
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut manifest = Manifest { version: 1, dirs: Vec::new(), files: Vec::new() };
+        let mut total_size = 0;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+
+        for entry in filtered_entries(source_path, &filter) {
+            let (entry, relative) = entry?;
+
+            if entry.file_type().is_dir() {
+                manifest.dirs.push(relative);
+                continue;
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let mut file = File::open(entry.path())?;
+            let metadata = file.metadata()?;
+            let mut chunks = Vec::new();
+            let mut size = 0;
+
+            loop {
+                let read = read_full(&mut file, &mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                let data = &buffer[..read];
+                let hash = hex::encode(Sha256::digest(data));
+                store_chunk(&hash, data)?;
+                chunks.push(hash);
+                size += read as u64;
+                if read < CHUNK_SIZE {
+                    break;
+                }
+            }
+
+            total_size += size;
+            manifest.files.push(ManifestFile {
+                path: relative,
+                size,
+                mode: file_mode(&metadata),
+                mtime: file_mtime(&metadata),
+                chunks,
+            });
+        }
+
+        write_manifest(Path::new(&manifest_path), &manifest)?;
+        Ok(total_size)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Rebuild the files of a manifest into `dest_dir`, overwriting existing files.
+/// With `only`, just that path (file or directory) is restored.
+pub async fn restore_snapshot(manifest_path: String, dest_dir: String, only: Option<String>) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        let manifest = read_manifest(Path::new(&manifest_path))?;
+        let dest_path = Path::new(&dest_dir);
+        fs::create_dir_all(dest_path)?;
+
+        let selected = |path: &str| only.as_deref().is_none_or(|o| is_within(path, o));
+
+        for dir in manifest.dirs.iter().filter(|d| selected(d)) {
+            fs::create_dir_all(dest_path.join(safe_relative(dir)?))?;
+        }
+
+        for entry in manifest.files.iter().filter(|f| selected(&f.path)) {
+            let target = dest_path.join(safe_relative(&entry.path)?);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut out = File::create(&target)?;
+            for hash in &entry.chunks {
+                let chunk = File::open(chunk_path(hash)).map_err(|e| {
+                    AppError::Internal(format!("Missing chunk {hash} for {}: {e}", entry.path))
+                        .with_code(ErrorCode::BackupRestoreFailed)
+                })?;
+                io::copy(&mut GzDecoder::new(chunk), &mut out)?;
+            }
+            out.flush()?;
+            set_file_mode(&target, entry.mode);
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Check that every chunk of a manifest exists, decompresses and matches its hash,
+/// and that the chunks add up to each file's size
+pub async fn verify_snapshot(manifest_path: PathBuf) -> Result<(), AppError> {
+    tokio::task::spawn_blocking(move || {
+        let manifest = read_manifest(&manifest_path)?;
+        let mut verified: HashSet<&str> = HashSet::new();
+        let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+
+        for entry in &manifest.files {
+            let mut size = 0u64;
+            for hash in &entry.chunks {
+                let corrupt = |reason: String| AppError::Internal(format!("Chunk {hash} of {}: {reason}", entry.path))
+                    .with_code(ErrorCode::BackupCorrupted);
+
+                buffer.clear();
+                let chunk = File::open(chunk_path(hash)).map_err(|e| corrupt(e.to_string()))?;
+                GzDecoder::new(chunk).read_to_end(&mut buffer).map_err(|e| corrupt(e.to_string()))?;
+                size += buffer.len() as u64;
+
+                if verified.insert(hash.as_str()) && hex::encode(Sha256::digest(&buffer)) != *hash {
+                    return Err(corrupt("content does not match its hash".into()));
+                }
+            }
+            if size != entry.size {
+                return Err(AppError::Internal(format!("{}: expected {} bytes, chunks hold {size}", entry.path, entry.size))
+                    .with_code(ErrorCode::BackupCorrupted));
+            }
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Write the files of a manifest as a self-contained tar.gz, for destinations without the chunk store
+pub async fn export_archive(manifest_path: PathBuf, archive_path: PathBuf) -> Result<u64, AppError> {
+    tokio::task::spawn_blocking(move || {
+        let manifest = read_manifest(&manifest_path)?;
+        // Taken from the manifest so exporting the same snapshot twice gives the same bytes
+        let mtime = file_mtime(&std::fs::metadata(&manifest_path)?).unwrap_or(0);
+        let mut tar = tar::Builder::new(GzEncoder::new(File::create(&archive_path)?, Compression::default()));
+
+        for dir in &manifest.dirs {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            header.set_mode(0o755);
+            header.set_mtime(mtime);
+            tar.append_data(&mut header, safe_relative(dir)?, io::empty())?;
+        }
+
+        for entry in &manifest.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(entry.size);
+            header.set_mode(entry.mode.unwrap_or(0o644) & 0o7777);
+            header.set_mtime(entry.mtime.unwrap_or(mtime));
+            tar.append_data(&mut header, safe_relative(&entry.path)?, ChunkReader::new(&entry.chunks))?;
+        }
+
+        tar.into_inner()?.finish()?;
+        Ok(std::fs::metadata(&archive_path)?.len())
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+/// Content of a file of a manifest
+pub fn file_reader(file: &ManifestFile) -> impl Read + '_ {
+    ChunkReader::new(&file.chunks)
+}
+
+/// Reads the decompressed content of a list of chunks as one stream
+struct ChunkReader<'a> {
+    hashes: std::slice::Iter<'a, String>,
+    current: Option<GzDecoder<File>>,
+}
+
+impl<'a> ChunkReader<'a> {
+    fn new(hashes: &'a [String]) -> Self {
+        Self { hashes: hashes.iter(), current: None }
+    }
+}
+
+impl Read for ChunkReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.current.is_none() {
+                let Some(hash) = self.hashes.next() else { return Ok(0) };
+                self.current = Some(GzDecoder::new(File::open(chunk_path(hash))?));
+            }
+            if let Some(chunk) = self.current.as_mut() {
+                match chunk.read(buf)? {
+                    0 => self.current = None,
+                    read => return Ok(read),
+                }
+            }
+        }
+    }
+}
+
+/// Delete every chunk no longer referenced by a manifest. Returns the number of deleted chunks.
+pub async fn collect_garbage() -> Result<usize, AppError> {
+    tokio::task::spawn_blocking(|| {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let chunks_root = chunks_dir();
+        if !chunks_root.exists() {
+            return Ok(0);
+        }
+
+        let mut referenced = HashSet::new();
+        for entry in fs::read_dir(backups_dir())? {
+            let path = entry?.path();
+            let is_manifest = path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(MANIFEST_SUFFIX));
+            if is_manifest {
+                // An unreadable manifest aborts the collection rather than risk deleting its chunks
+                let manifest = read_manifest(&path)?;
+                referenced.extend(manifest.files.into_iter().flat_map(|f| f.chunks));
+            }
+        }
+
+        let mut deleted = 0;
+        for entry in WalkDir::new(&chunks_root).min_depth(2).max_depth(2) {
+            let entry = entry.map_err(|e| AppError::Internal(format!("Failed to walk chunk store: {e}")))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy();
+            if !referenced.contains(name.as_ref()) {
+                fs::remove_file(entry.path())?;
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+}
+
+fn store_chunk(hash: &str, data: &[u8]) -> Result<(), AppError> {
+    let path = chunk_path(hash);
+    if path.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write then rename so an interrupted backup never leaves a truncated chunk behind
+    let tmp_path = path.with_extension("tmp");
+    let mut encoder = GzEncoder::new(File::create(&tmp_path)?, Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+pub fn read_manifest(path: &Path) -> Result<Manifest, AppError> {
+    let content = fs::read(path)?;
+    serde_json::from_slice(&content)
+        .map_err(|e| AppError::Internal(format!("Invalid backup manifest {}: {e}", path.display())))
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_vec(manifest)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize manifest: {e}")))?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Fill `buffer` as much as possible; returns fewer bytes only at end of file
+fn read_full(file: &mut File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: Option<u32>) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(mode) = mode {
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+    }
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: Option<u32>) {}
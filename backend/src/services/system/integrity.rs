@@ -0,0 +1,111 @@
+// Backup integrity checks
+// A backup is re-read end to end and its checksum compared with the one stored at creation.
+// The outcome is kept on the backup row so the API can flag corrupted backups.
+
+use sqlx::FromRow;
+use tracing::{error, info};
+
+use super::backup;
+use super::discord;
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+
+pub const STATUS_OK: &str = "ok";
+pub const STATUS_CORRUPTED: &str = "corrupted";
+
+/// Backups younger than this are re-verified by the scheduler
+const RECENT_DAYS: i64 = 7;
+/// Minimum delay between two automatic verifications of the same backup
+const REVERIFY_AFTER_HOURS: i64 = 24;
+/// Backups verified per scheduler run, to bound the disk load
+const BATCH_SIZE: i64 = 5;
+
+pub struct Verification {
+    pub status: &'static str,
+    pub error: Option<String>,
+}
+
+/// Verify a backup and store the outcome. Backups created before checksums existed adopt the computed one.
+pub async fn verify(
+    pool: &DbPool,
+    id: &str,
+    filename: &str,
+    kind: &str,
+    expected_checksum: Option<&str>,
+) -> Result<Verification, AppError> {
+    let (status, error, checksum) = match backup::verify_backup(filename, kind).await {
+        Ok(actual) => match expected_checksum {
+            Some(expected) if !expected.eq_ignore_ascii_case(&actual) => (
+                STATUS_CORRUPTED,
+                Some(format!("Checksum mismatch: expected {expected}, got {actual}")),
+                None,
+            ),
+            _ => (STATUS_OK, None, Some(actual)),
+        },
+        Err(e) => (STATUS_CORRUPTED, Some(e.to_string()), None),
+    };
+
+    sqlx::query(
+        "UPDATE backups SET verify_status = ?, verify_error = ?, verified_at = ?, checksum = COALESCE(checksum, ?) WHERE id = ?"
+    )
+    .bind(status)
+    .bind(&error)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .bind(checksum)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(Verification { status, error })
+}
+
+#[derive(FromRow)]
+struct PendingVerification {
+    id: String,
+    server_id: String,
+    filename: String,
+    kind: String,
+    checksum: Option<String>,
+    server_name: String,
+    discord_webhook_url: Option<String>,
+}
+
+/// Scheduler job: verify a few recent backups that were not checked lately
+pub async fn verify_recent(pool: &DbPool) -> Result<(), AppError> {
+    let now = chrono::Utc::now();
+    let created_after = (now - chrono::Duration::days(RECENT_DAYS)).to_rfc3339();
+    let verified_before = (now - chrono::Duration::hours(REVERIFY_AFTER_HOURS)).to_rfc3339();
+
+    let backups: Vec<PendingVerification> = sqlx::query_as(
+        "SELECT b.id, b.server_id, b.filename, b.kind, b.checksum, s.name AS server_name, s.discord_webhook_url
+         FROM backups b JOIN servers s ON s.id = b.server_id
+         WHERE b.created_at >= ? AND (b.verified_at IS NULL OR b.verified_at < ?)
+         ORDER BY b.created_at DESC LIMIT ?"
+    )
+    .bind(&created_after)
+    .bind(&verified_before)
+    .bind(BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    for b in backups {
+        let verification = verify(pool, &b.id, &b.filename, &b.kind, b.checksum.as_deref()).await?;
+        if verification.status == STATUS_OK {
+            info!("Backup {} of server {} verified", b.filename, b.server_id);
+            continue;
+        }
+
+        let reason = verification.error.unwrap_or_default();
+        error!("Backup {} of server {} is corrupted: {}", b.filename, b.server_id, reason);
+        discord::send_notification(
+            pool,
+            "⚠️ Sauvegarde Corrompue",
+            &format!("La sauvegarde **{}** du serveur **{}** est corrompue : {reason}", b.filename, b.server_name),
+            discord::COLOR_ERROR,
+            Some(&b.server_name),
+            b.discord_webhook_url.as_deref().filter(|u| !u.is_empty()),
+        ).await;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,10 @@
 pub mod backup;
+pub mod chunk_store;
+pub mod restore;
+pub mod replication;
+pub mod integrity;
+pub mod browse;
+pub mod storage;
 pub mod discord;
 pub mod scheduler;
+pub mod autostart;
@@ -0,0 +1,216 @@
+// Backup replication to remote destinations
+// Once a backup is recorded it is copied to every destination listed in the server's
+// `backup_replication` rules. Each destination keeps its own number of copies, independently
+// of the local retention (`backup_max_backups`).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tracing::{error, info};
+
+use super::backup::{self, backups_dir, KIND_INCREMENTAL};
+use super::chunk_store::{self, MANIFEST_SUFFIX};
+use super::storage::{DestinationConfig, StorageTarget};
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationRule {
+    pub destination_id: String,
+    /// Copies kept on this destination
+    pub keep: u32,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ReplicaRow {
+    pub id: String,
+    pub server_id: String,
+    pub destination_id: String,
+    pub filename: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+}
+
+pub fn rules_from_column(column: Option<&str>) -> Vec<ReplicationRule> {
+    column
+        .and_then(|r| serde_json::from_str(r).ok())
+        .unwrap_or_default()
+}
+
+pub async fn load_destination(pool: &DbPool, id: &str) -> Result<DestinationConfig, AppError> {
+    let config: String = sqlx::query_scalar("SELECT config FROM backup_destinations WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("backups.destination_not_found".into())
+            .with_code(ErrorCode::BackupDestinationNotFound))?;
+
+    serde_json::from_str(&config)
+        .map_err(|e| AppError::Internal(format!("Invalid destination {id}: {e}")))
+}
+
+/// Location of a replica on its destination
+pub fn replica_key(server_id: &str, filename: &str) -> String {
+    format!("{server_id}/{filename}")
+}
+
+/// Copy a freshly recorded backup to the server's destinations, in the background
+pub fn spawn_replication(pool: DbPool, server_id: String, filename: String, kind: String) {
+    tokio::spawn(async move {
+        if let Err(e) = replicate(&pool, &server_id, &filename, &kind).await {
+            error!("Replication of backup {} failed: {}", filename, e);
+        }
+    });
+}
+
+async fn replicate(pool: &DbPool, server_id: &str, filename: &str, kind: &str) -> Result<(), AppError> {
+    let column: Option<String> = sqlx::query_scalar("SELECT backup_replication FROM servers WHERE id = ?")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    let rules = rules_from_column(column.as_deref());
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    // Destinations have no chunk store: incremental backups travel as plain archives
+    let local_path = backups_dir().join(filename);
+    let (upload_path, remote_name, exported) = if kind == KIND_INCREMENTAL {
+        let name = format!("{}.tar.gz", filename.trim_end_matches(MANIFEST_SUFFIX));
+        let export_path = backups_dir().join(format!(".export-{name}"));
+        if let Err(e) = chunk_store::export_archive(local_path, export_path.clone()).await {
+            let _ = tokio::fs::remove_file(&export_path).await;
+            return Err(e);
+        }
+        (export_path.clone(), name, Some(export_path))
+    } else {
+        (local_path, filename.to_string(), None)
+    };
+
+    for rule in &rules {
+        if let Err(e) = replicate_to(pool, server_id, rule, &upload_path, &remote_name).await {
+            error!("Replication of backup {} to destination {} failed: {}", filename, rule.destination_id, e);
+        }
+    }
+
+    if let Some(path) = exported {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    Ok(())
+}
+
+async fn replicate_to(
+    pool: &DbPool,
+    server_id: &str,
+    rule: &ReplicationRule,
+    upload_path: &Path,
+    remote_name: &str,
+) -> Result<(), AppError> {
+    let target = load_destination(pool, &rule.destination_id).await?.target();
+    let size = tokio::fs::metadata(upload_path).await?.len();
+
+    target.upload(upload_path, &replica_key(server_id, remote_name)).await?;
+
+    sqlx::query(
+        "INSERT INTO backup_replicas (id, server_id, destination_id, filename, size_bytes, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(server_id)
+    .bind(&rule.destination_id)
+    .bind(remote_name)
+    .bind(size as i64)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    let pruned = prune_replicas(pool, target.as_ref(), server_id, &rule.destination_id, rule.keep.max(1) as usize).await?;
+    info!(
+        "Backup {} replicated to destination {} ({} old replica(s) pruned)",
+        remote_name, rule.destination_id, pruned
+    );
+    Ok(())
+}
+
+/// Delete the oldest replicas of a server on a destination, keeping the `keep` most recent
+async fn prune_replicas(
+    pool: &DbPool,
+    target: &dyn StorageTarget,
+    server_id: &str,
+    destination_id: &str,
+    keep: usize,
+) -> Result<usize, AppError> {
+    let replicas: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, filename FROM backup_replicas WHERE server_id = ? AND destination_id = ? ORDER BY created_at DESC"
+    )
+    .bind(server_id)
+    .bind(destination_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut pruned = 0;
+    for (id, filename) in replicas.into_iter().skip(keep) {
+        delete_replica(pool, target, &id, server_id, &filename).await?;
+        pruned += 1;
+    }
+    Ok(pruned)
+}
+
+pub async fn delete_replica(
+    pool: &DbPool,
+    target: &dyn StorageTarget,
+    id: &str,
+    server_id: &str,
+    filename: &str,
+) -> Result<(), AppError> {
+    target.delete(&replica_key(server_id, filename)).await?;
+    sqlx::query("DELETE FROM backup_replicas WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Download a replica back into the local backups directory once it reads back as a valid archive.
+/// Returns its size in bytes and its checksum.
+pub async fn fetch_replica(pool: &DbPool, replica: &ReplicaRow) -> Result<(u64, String), AppError> {
+    let target = load_destination(pool, &replica.destination_id).await?.target();
+    let local_path = backups_dir().join(backup::safe_relative(&replica.filename)?);
+    if local_path.exists() {
+        return Err(AppError::BadRequest("backups.already_local".into()));
+    }
+
+    // The copy comes back from another host: it is checked like an uploaded archive before use
+    let part_path = backups_dir().join(format!(".fetch-{}", replica.filename));
+    let fetched = async {
+        target.download(&replica_key(&replica.server_id, &replica.filename), &part_path).await?;
+        backup::validate_import(part_path.clone()).await
+    };
+    let checksum = match fetched.await {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(e);
+        }
+    };
+    tokio::fs::rename(&part_path, &local_path).await?;
+
+    Ok((tokio::fs::metadata(&local_path).await?.len(), checksum))
+}
+
+/// Write and remove a small probe file, to check a destination's settings
+pub async fn test_destination(config: &DestinationConfig) -> Result<(), AppError> {
+    let target = config.target();
+    let probe_path = backups_dir().join(format!(".probe-{}", uuid::Uuid::new_v4()));
+    tokio::fs::write(&probe_path, b"draveur").await?;
+
+    let key = ".draveur-probe";
+    let result = match target.upload(&probe_path, key).await {
+        Ok(()) => target.delete(key).await,
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&probe_path).await;
+    result
+}
@@ -0,0 +1,227 @@
+// Safe backup restore
+// The backup is extracted into a staging directory next to the working directory, the current
+// state is snapshotted, then directories are swapped with renames. Any failure rolls back.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+use super::backup::{self, relative_path, safe_relative, BackupFilter};
+use crate::api::servers::models::ServerRow;
+use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+/// Restore a backup into the server's working directory.
+/// With `only`, just that relative path is replaced. Returns the id of the pre-restore snapshot,
+/// if there was anything to snapshot.
+pub async fn safe_restore(
+    pool: &DbPool,
+    server: &ServerRow,
+    filename: &str,
+    kind: &str,
+    only: Option<String>,
+) -> Result<Option<String>, AppError> {
+    if let Some(path) = &only {
+        safe_relative(path)?;
+    }
+
+    let working_dir = PathBuf::from(&server.working_dir);
+    let staging = sibling_path(&working_dir, "restore-staging")?;
+    let aside = sibling_path(&working_dir, "pre-restore")?;
+
+    // Leftovers of an interrupted restore
+    for dir in [&staging, &aside] {
+        if dir.exists() {
+            tokio::fs::remove_dir_all(dir).await?;
+        }
+    }
+
+    // 1. Extract into staging and verify
+    let extracted = backup::extract_backup(filename, kind, staging.to_string_lossy().to_string(), only.clone()).await;
+    if let Err(e) = extracted.and_then(|_| verify_staging(&staging, only.as_deref())) {
+        let _ = tokio::fs::remove_dir_all(&staging).await;
+        return Err(AppError::Internal(format!("Restore failed: {e}")).with_code(ErrorCode::BackupRestoreFailed));
+    }
+
+    // 2. Snapshot the current state so the restore itself can be undone
+    let snapshot_id = match snapshot_current_state(pool, server).await {
+        Ok(id) => id,
+        Err(e) => {
+            let _ = tokio::fs::remove_dir_all(&staging).await;
+            return Err(e);
+        }
+    };
+
+    // 3. Swap, rolling back on failure
+    let filter = server.backup_filter()?;
+    let swap_working_dir = working_dir.clone();
+    let swap_staging = staging.clone();
+    let swap_aside = aside.clone();
+    let swapped = tokio::task::spawn_blocking(move || match only {
+        Some(path) => swap_path(&swap_working_dir, &swap_staging, &path),
+        None => swap_directories(&swap_working_dir, &swap_staging, &swap_aside, &filter),
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?;
+
+    let _ = tokio::fs::remove_dir_all(&staging).await;
+    if let Err(e) = swapped {
+        return Err(AppError::Internal(format!("Restore failed, previous state kept: {e}"))
+            .with_code(ErrorCode::BackupRestoreFailed));
+    }
+
+    if aside.exists() {
+        tokio::spawn(async move {
+            if let Err(e) = tokio::fs::remove_dir_all(&aside).await {
+                warn!("Failed to remove previous server directory {}: {}", aside.display(), e);
+            }
+        });
+    }
+
+    info!("Restored backup {} into server {}", filename, server.id);
+    Ok(snapshot_id)
+}
+
+/// Hidden directory next to the working directory, on the same filesystem so renames are atomic
+fn sibling_path(working_dir: &Path, suffix: &str) -> Result<PathBuf, AppError> {
+    let name = working_dir.file_name()
+        .ok_or_else(|| AppError::Internal(format!("Invalid working directory: {}", working_dir.display())))?;
+    let parent = working_dir.parent().unwrap_or(Path::new("."));
+    Ok(parent.join(format!(".{}.{suffix}", name.to_string_lossy())))
+}
+
+fn verify_staging(staging: &Path, only: Option<&str>) -> Result<(), AppError> {
+    match only {
+        Some(path) if !staging.join(path).exists() => Err(AppError::NotFound(format!("Path not found in backup: {path}"))
+            .with_code(ErrorCode::BackupPathNotFound)),
+        None if fs::read_dir(staging).map(|mut d| d.next().is_none()).unwrap_or(true) => {
+            Err(AppError::Internal("Backup is empty".into()).with_code(ErrorCode::BackupRestoreFailed))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn snapshot_current_state(pool: &DbPool, server: &ServerRow) -> Result<Option<String>, AppError> {
+    if !Path::new(&server.working_dir).exists() {
+        return Ok(None);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let created = backup::create_backup(
+        &server.id,
+        server.working_dir.clone(),
+        "pre_restore",
+        &server.backup_mode,
+        server.backup_filter()?,
+    ).await?;
+
+    sqlx::query("INSERT INTO backups (id, server_id, filename, size_bytes, kind, checksum, origin, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&server.id)
+        .bind(&created.filename)
+        .bind(created.size as i64)
+        .bind(&server.backup_mode)
+        .bind(&created.checksum)
+        .bind(backup::ORIGIN_PRE_RESTORE)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+    Ok(Some(id))
+}
+
+/// Replace the working directory with the staging one. Paths the backup rules exclude
+/// (server binaries, assets, logs) are not in the backup, so they are carried over first.
+fn swap_directories(working_dir: &Path, staging: &Path, aside: &Path, filter: &BackupFilter) -> Result<(), AppError> {
+    if !working_dir.exists() {
+        fs::rename(staging, working_dir)?;
+        return Ok(());
+    }
+
+    let mut carried: Vec<String> = Vec::new();
+    let result = (|| -> Result<(), AppError> {
+        for relative in excluded_roots(working_dir, filter)? {
+            let target = staging.join(&relative);
+            if target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(working_dir.join(&relative), &target)?;
+            carried.push(relative);
+        }
+
+        fs::rename(working_dir, aside)?;
+        if let Err(e) = fs::rename(staging, working_dir) {
+            fs::rename(aside, working_dir)?;
+            return Err(e.into());
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        for relative in carried.iter().rev() {
+            if let Err(e) = fs::rename(staging.join(relative), working_dir.join(relative)) {
+                warn!("Rollback: failed to move back {}: {}", relative, e);
+            }
+        }
+    }
+    result
+}
+
+/// Top-most paths of `root` that the backup rules leave out
+fn excluded_roots(root: &Path, filter: &BackupFilter) -> Result<Vec<String>, AppError> {
+    let mut roots = Vec::new();
+    let mut walker = WalkDir::new(root).min_depth(1).follow_links(false).into_iter();
+
+    while let Some(entry) = walker.next() {
+        let entry = entry.map_err(|e| AppError::Internal(format!("Failed to walk directory: {e}")))?;
+        let relative = relative_path(root, entry.path())?;
+
+        if entry.file_type().is_dir() {
+            if !filter.allows_dir(&relative) {
+                roots.push(relative);
+                walker.skip_current_dir();
+            }
+        } else if !filter.allows_file(&relative) {
+            roots.push(relative);
+        }
+    }
+
+    Ok(roots)
+}
+
+/// Replace a single path of the working directory with its staged version
+fn swap_path(working_dir: &Path, staging: &Path, relative: &str) -> Result<(), AppError> {
+    let source = staging.join(relative);
+    let target = working_dir.join(relative);
+    let aside = sibling_path(&target, "pre-restore")?;
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let had_target = target.exists();
+    if had_target {
+        fs::rename(&target, &aside)?;
+    }
+
+    if let Err(e) = fs::rename(&source, &target) {
+        if had_target {
+            fs::rename(&aside, &target)?;
+        }
+        return Err(e.into());
+    }
+
+    if had_target {
+        let removed = if aside.is_dir() { fs::remove_dir_all(&aside) } else { fs::remove_file(&aside) };
+        if let Err(e) = removed {
+            warn!("Failed to remove previous version of {}: {}", relative, e);
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time;
 use sysinfo::{System, RefreshKind, CpuRefreshKind, MemoryRefreshKind};
@@ -6,9 +8,12 @@ use tracing::{info, error};
 use std::str::FromStr;
 use cron::Schedule;
 
+use crate::api::servers::models::ServerRow;
 use crate::core::database::DbPool;
+use crate::core::error::AppError;
+use crate::services::game::{container, logs};
 use crate::services::game::manager::ProcessManager;
-use crate::services::system::discord;
+use crate::services::system::{backup, discord, integrity, replication};
 
 pub fn start(pool: DbPool, process_manager: ProcessManager) {
     let pool_clone = pool.clone();
@@ -34,24 +39,79 @@ pub fn start(pool: DbPool, process_manager: ProcessManager) {
         }
     });
 
+    start_backup_verifier(pool.clone());
+    start_log_maintenance(pool.clone());
     start_task_scheduler(pool, process_manager);
 }
 
+fn start_backup_verifier(pool: DbPool) {
+    tokio::spawn(async move {
+        // Let the panel settle before reading archives
+        time::sleep(Duration::from_secs(300)).await;
+        let mut interval = time::interval(Duration::from_secs(3600));
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = integrity::verify_recent(&pool).await {
+                error!("Error in backup verification: {e}");
+            }
+        }
+    });
+}
+
+/// Rotate oversized logs, compress rotated ones and apply each server's `logs_retention_days`
+fn start_log_maintenance(pool: DbPool) {
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(60));
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = maintain_logs(&pool).await {
+                error!("Error in log maintenance: {e}");
+            }
+        }
+    });
+}
+
+async fn maintain_logs(pool: &DbPool) -> anyhow::Result<()> {
+    let servers: Vec<(String, String, i64, String)> =
+        sqlx::query_as("SELECT id, working_dir, logs_retention_days, execution_backend FROM servers")
+            .fetch_all(pool)
+            .await?;
+
+    tokio::task::spawn_blocking(move || {
+        for (id, working_dir, retention_days, backend) in servers {
+            let retention_days = u32::try_from(retention_days).unwrap_or(0);
+            // The console log of a container is written, and rotated, by the panel itself
+            let writer_rotates = backend == container::BACKEND_DOCKER;
+            if let Err(e) = logs::maintain(std::path::Path::new(&working_dir), retention_days, writer_rotates) {
+                error!("Log maintenance failed for server {}: {}", id, e);
+            }
+        }
+    })
+    .await?;
+    Ok(())
+}
+
 fn start_task_scheduler(pool: DbPool, pm: ProcessManager) {
     tokio::spawn(async move {
         // Run every minute at :00
         let mut interval = time::interval(Duration::from_secs(60));
+        let backups_in_progress: Arc<Mutex<HashSet<String>>> = Arc::default();
         
         loop {
             interval.tick().await;
-            if let Err(e) = check_and_run_tasks(&pool, &pm).await {
+            if let Err(e) = check_and_run_tasks(&pool, &pm, &backups_in_progress).await {
                 error!("Error in task scheduler: {e}");
             }
+            if let Err(e) = run_backup_policies(&pool, &pm, &backups_in_progress).await {
+                error!("Error in backup policy: {e}");
+            }
         }
     });
 }
 
-async fn check_and_run_tasks(pool: &DbPool, pm: &ProcessManager) -> anyhow::Result<()> {
+async fn check_and_run_tasks(pool: &DbPool, pm: &ProcessManager, backups_in_progress: &Arc<Mutex<HashSet<String>>>) -> anyhow::Result<()> {
     let now = Local::now();
     let now_time = now.format("%H:%M").to_string();
     
@@ -118,7 +178,9 @@ async fn check_and_run_tasks(pool: &DbPool, pm: &ProcessManager) -> anyhow::Resu
                             srv.extra_args.as_deref(),
                             config_json.as_ref(),
                             &srv.game_type,
-                            srv.nice_level
+                            srv.nice_level,
+                            srv.container_settings().as_ref(),
+                            &srv.resource_limits(),
                         ).await; 
                     },
                     "stop" => { let _ = pm.stop(&s.server_id).await; },
@@ -133,31 +195,29 @@ async fn check_and_run_tasks(pool: &DbPool, pm: &ProcessManager) -> anyhow::Resu
                             srv.extra_args.as_deref(),
                             config_json.as_ref(),
                             &srv.game_type,
-                            srv.nice_level
+                            srv.nice_level,
+                            srv.container_settings().as_ref(),
+                            &srv.resource_limits(),
                         ).await; 
                     },
                     "backup" => {
-                        let filename = format!("backup_{}_{}.tar.gz", s.server_id, Utc::now().format("%Y%m%d_%H%M%S"));
-                        let backup_path = format!("backups/{filename}");
-                        
-                        // Use the async backup service
-                        let working_dir = srv.working_dir.clone();
-                        let backup_path_clone = backup_path.clone();
-                        let pool_clone = pool.clone();
-                        let s_id = s.server_id.clone();
-                        
-                        tokio::spawn(async move {
-                            if let Ok(size) = crate::services::system::backup::create_archive(working_dir, backup_path_clone).await {
-                                 let _ = sqlx::query("INSERT INTO backups (id, server_id, filename, size_bytes, created_at) VALUES (?, ?, ?, ?, ?)")
-                                    .bind(uuid::Uuid::new_v4().to_string())
-                                    .bind(&s_id)
-                                    .bind(&filename)
-                                    .bind(size as i64)
-                                    .bind(Utc::now().to_rfc3339())
-                                    .execute(&pool_clone)
-                                    .await;
-                            }
-                        });
+                        // Shares the guard of the backup policies: two backups of a server never overlap
+                        let claimed = backups_in_progress.lock().map(|mut set| set.insert(srv.id.clone())).unwrap_or(false);
+                        if claimed {
+                            let pool = pool.clone();
+                            let pm = pm.clone();
+                            let in_progress = backups_in_progress.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = run_automatic_backup(&pool, &pm, &srv).await {
+                                    error!("Scheduled backup failed for server {}: {e}", srv.id);
+                                }
+                                if let Ok(mut set) = in_progress.lock() {
+                                    set.remove(&srv.id);
+                                }
+                            });
+                        } else {
+                            info!("Backup of server {} already running, scheduled task '{}' skipped", srv.id, s.name);
+                        }
                     },
                     _ => {}
                 }
@@ -174,6 +234,89 @@ async fn check_and_run_tasks(pool: &DbPool, pm: &ProcessManager) -> anyhow::Resu
     Ok(())
 }
 
+/// Back up servers whose `backup_frequency` (minutes) has elapsed since their last backup
+async fn run_backup_policies(pool: &DbPool, pm: &ProcessManager, in_progress: &Arc<Mutex<HashSet<String>>>) -> anyhow::Result<()> {
+    let servers: Vec<ServerRow> = sqlx::query_as(
+        "SELECT * FROM servers WHERE backup_enabled = 1 AND backup_frequency > 0"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let now = Utc::now();
+
+    for server in servers {
+        let id = server.id.clone();
+        if pm.is_installing(&id) || !std::path::Path::new(&server.working_dir).exists() {
+            continue;
+        }
+
+        let last: Option<(String,)> = sqlx::query_as(
+            "SELECT created_at FROM backups WHERE server_id = ? ORDER BY created_at DESC LIMIT 1"
+        )
+        .bind(&id)
+        .fetch_optional(pool)
+        .await?;
+
+        let due = match last.and_then(|(created_at,)| DateTime::parse_from_rfc3339(&created_at).ok()) {
+            Some(last) => now.signed_duration_since(last) >= chrono::Duration::minutes(server.backup_frequency as i64),
+            None => true,
+        };
+        if !due {
+            continue;
+        }
+
+        // Archiving can outlast a tick; never run two backups of the same server at once
+        let claimed = in_progress.lock().map(|mut set| set.insert(id.clone())).unwrap_or(false);
+        if !claimed {
+            continue;
+        }
+
+        let pool = pool.clone();
+        let pm = pm.clone();
+        let in_progress = in_progress.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_automatic_backup(&pool, &pm, &server).await {
+                error!("Automatic backup failed for server {id}: {e}");
+            }
+            if let Ok(mut set) = in_progress.lock() {
+                set.remove(&id);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Back up a server with its backup settings, prune its old scheduled backups and replicate the new one
+pub async fn run_automatic_backup(pool: &DbPool, pm: &ProcessManager, server: &ServerRow) -> Result<(), AppError> {
+    let server_id = server.id.as_str();
+    let created = pm.with_saving_paused(server_id, backup::create_backup(
+        server_id,
+        server.working_dir.clone(),
+        &server.backup_prefix,
+        &server.backup_mode,
+        server.backup_filter()?,
+    )).await?;
+
+    sqlx::query("INSERT INTO backups (id, server_id, filename, size_bytes, kind, checksum, origin, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(server_id)
+        .bind(&created.filename)
+        .bind(created.size as i64)
+        .bind(&server.backup_mode)
+        .bind(&created.checksum)
+        .bind(backup::ORIGIN_SCHEDULED)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+
+    let pruned = backup::prune_backups(pool, server_id, server.backup_max_backups.max(1) as usize).await?;
+    info!("Automatic backup {} created for server {} ({} old backup(s) pruned)", created.filename, server_id, pruned);
+    replication::spawn_replication(pool.clone(), server_id.to_string(), created.filename, server.backup_mode.clone());
+
+    Ok(())
+}
+
 async fn run_status_update(pool: &DbPool, sys: &mut System, pm: &ProcessManager) -> anyhow::Result<()> {
     sys.refresh_cpu_all();
     sys.refresh_memory();
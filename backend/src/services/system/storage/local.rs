@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+use axum::async_trait;
+
+use super::{transfer_error, StorageTarget};
+use crate::core::error::AppError;
+use crate::services::system::backup::safe_relative;
+
+/// Another directory on the panel host, e.g. a mounted NAS share
+pub struct LocalTarget {
+    root: PathBuf,
+}
+
+impl LocalTarget {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        Ok(self.root.join(safe_relative(key)?))
+    }
+}
+
+#[async_trait]
+impl StorageTarget for LocalTarget {
+    async fn upload(&self, local_path: &Path, key: &str) -> Result<(), AppError> {
+        let target = self.path(key)?;
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| transfer_error("upload", key, e))?;
+        }
+
+        // Copy under a temporary name so a partial copy is never mistaken for a backup
+        let tmp = target.with_extension("part");
+        tokio::fs::copy(local_path, &tmp).await.map_err(|e| transfer_error("upload", key, e))?;
+        tokio::fs::rename(&tmp, &target).await.map_err(|e| transfer_error("upload", key, e))?;
+        Ok(())
+    }
+
+    async fn download(&self, key: &str, local_path: &Path) -> Result<(), AppError> {
+        tokio::fs::copy(self.path(key)?, local_path).await.map_err(|e| transfer_error("download", key, e))?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(transfer_error("delete", key, e)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Backup storage targets
+// A destination is where finished backups are copied to: another local directory
+// (NAS mount, second disk), an SFTP server or an S3-compatible bucket.
+
+mod local;
+mod s3;
+mod sftp;
+
+use std::path::Path;
+
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::AppError;
+use crate::core::error::codes::ErrorCode;
+
+pub use local::LocalTarget;
+pub use s3::S3Target;
+pub use sftp::SftpTarget;
+
+#[async_trait]
+pub trait StorageTarget: Send + Sync {
+    /// Copy a local file to `key` (a '/'-separated path below the destination root)
+    async fn upload(&self, local_path: &Path, key: &str) -> Result<(), AppError>;
+    /// Copy `key` to a local file
+    async fn download(&self, key: &str, local_path: &Path) -> Result<(), AppError>;
+    /// Remove `key`; removing a missing key is not an error
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Destination settings, stored as JSON in `backup_destinations.config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DestinationConfig {
+    Local {
+        path: String,
+    },
+    Sftp {
+        host: String,
+        port: Option<u16>,
+        username: String,
+        password: Option<String>,
+        /// Path to a private key on the panel host, used instead of the password
+        private_key_path: Option<String>,
+        /// Hex SHA-256 fingerprint of the host key, required (the panel never connects to an unknown key)
+        host_key_sha256: Option<String>,
+        path: String,
+    },
+    S3 {
+        endpoint: String,
+        region: Option<String>,
+        bucket: String,
+        access_key: String,
+        secret_key: Option<String>,
+        prefix: Option<String>,
+        /// `https://endpoint/bucket/key` instead of `https://bucket.endpoint/key` (MinIO)
+        path_style: Option<bool>,
+    },
+}
+
+impl DestinationConfig {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let missing = match self {
+            Self::Local { path } => path.trim().is_empty(),
+            Self::Sftp { host, username, path, password, private_key_path, .. } => {
+                host.trim().is_empty() || username.trim().is_empty() || path.trim().is_empty()
+                    || (password.is_none() && private_key_path.is_none())
+            }
+            Self::S3 { endpoint, bucket, access_key, secret_key, .. } => {
+                endpoint.trim().is_empty() || bucket.trim().is_empty() || access_key.trim().is_empty()
+                    || secret_key.is_none()
+            }
+        };
+        if missing {
+            return Err(AppError::BadRequest("backups.invalid_destination".into())
+                .with_code(ErrorCode::BackupInvalidDestination));
+        }
+        if let Self::Sftp { host_key_sha256, .. } = self {
+            let fingerprint = host_key_sha256.as_deref().map(str::trim).unwrap_or_default();
+            if fingerprint.len() != 64 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(AppError::BadRequest("backups.invalid_host_key".into())
+                    .with_code(ErrorCode::BackupInvalidDestination));
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy without the secrets, for API responses
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        match &mut config {
+            Self::Local { .. } => {}
+            Self::Sftp { password, .. } => *password = None,
+            Self::S3 { secret_key, .. } => *secret_key = None,
+        }
+        config
+    }
+
+    /// Secrets are never sent back to clients, so an update without them keeps the stored ones
+    pub fn keep_secrets_from(&mut self, previous: &Self) {
+        match (self, previous) {
+            (Self::Sftp { password, .. }, Self::Sftp { password: old, .. }) if password.is_none() => {
+                *password = old.clone();
+            }
+            (Self::S3 { secret_key, .. }, Self::S3 { secret_key: old, .. }) if secret_key.is_none() => {
+                *secret_key = old.clone();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn target(&self) -> Box<dyn StorageTarget> {
+        match self.clone() {
+            Self::Local { path } => Box::new(LocalTarget::new(path)),
+            Self::Sftp { host, port, username, password, private_key_path, host_key_sha256, path } => Box::new(SftpTarget {
+                host,
+                port: port.unwrap_or(22),
+                username,
+                password,
+                private_key_path,
+                host_key_sha256,
+                root: path,
+            }),
+            Self::S3 { endpoint, region, bucket, access_key, secret_key, prefix, path_style } => Box::new(S3Target::new(
+                endpoint,
+                region.unwrap_or_else(|| "us-east-1".into()),
+                bucket,
+                access_key,
+                secret_key.unwrap_or_default(),
+                prefix.unwrap_or_default(),
+                path_style.unwrap_or(true),
+            )),
+        }
+    }
+}
+
+pub(crate) fn transfer_error(action: &str, key: &str, e: impl std::fmt::Display) -> AppError {
+    AppError::Internal(format!("Failed to {action} {key}: {e}")).with_code(ErrorCode::BackupReplicationFailed)
+}
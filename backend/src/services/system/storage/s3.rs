@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use axum::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::{transfer_error, StorageTarget};
+use crate::core::error::AppError;
+use crate::services::system::backup::safe_relative;
+
+/// Files up to this size are sent with a single PUT, larger ones as a multipart upload of parts this size
+const PART_SIZE: usize = 64 * 1024 * 1024;
+
+/// S3-compatible bucket (AWS, MinIO, Backblaze B2, Wasabi...), signed with AWS Signature V4
+pub struct S3Target {
+    client: reqwest::Client,
+    endpoint: String,
+    region: String,
+    bucket: String,
+    access_key: String,
+    secret_key: String,
+    prefix: String,
+    path_style: bool,
+}
+
+impl S3Target {
+    pub fn new(
+        endpoint: String,
+        region: String,
+        bucket: String,
+        access_key: String,
+        secret_key: String,
+        prefix: String,
+        path_style: bool,
+    ) -> Self {
+        let endpoint = if endpoint.contains("://") { endpoint } else { format!("https://{endpoint}") };
+        let prefix = prefix.trim_matches('/');
+        let prefix = if prefix.is_empty() { String::new() } else { format!("{prefix}/") };
+
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            region,
+            bucket,
+            access_key,
+            secret_key,
+            prefix,
+            path_style,
+        }
+    }
+
+    /// Sign and send a request for `key`, failing on any non-2xx status
+    async fn send(&self, method: Method, key: &str, query: &[(&str, &str)], body: Vec<u8>) -> Result<reqwest::Response, String> {
+        safe_relative(key).map_err(|e| e.to_string())?;
+        let object = format!("{}{}", self.prefix, key);
+
+        let (scheme, authority) = self.endpoint.split_once("://").unwrap_or(("https", &self.endpoint));
+        let (host, path) = if self.path_style {
+            (authority.to_string(), format!("/{}/{}", uri_encode(&self.bucket, false), uri_encode(&object, true)))
+        } else {
+            (format!("{}.{authority}", self.bucket), format!("/{}", uri_encode(&object, true)))
+        };
+
+        let mut query: Vec<(String, String)> = query.iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join("&");
+
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let authorization = self.authorization(method.as_str(), &host, &path, &query, &amz_date, &payload_hash);
+
+        let url = if query.is_empty() {
+            format!("{scheme}://{host}{path}")
+        } else {
+            format!("{scheme}://{host}{path}?{query}")
+        };
+
+        let response = self.client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("{status}: {}", text.chars().take(300).collect::<String>()));
+        }
+        Ok(response)
+    }
+
+    /// SigV4 `Authorization` header over the host, x-amz-content-sha256 and x-amz-date headers
+    fn authorization(&self, method: &str, host: &str, path: &str, query: &str, amz_date: &str, payload_hash: &str) -> String {
+        let date = &amz_date[..8];
+        let canonical_request = format!(
+            "{method}\n{path}\n{query}\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload_hash}"
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(hmac(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes()), |key, part| hmac(&key, part.as_bytes()));
+        let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+            self.access_key
+        )
+    }
+
+    async fn upload_multipart(&self, file: &mut tokio::fs::File, key: &str) -> Result<(), String> {
+        let response = self.send(Method::POST, key, &[("uploads", "")], Vec::new()).await?;
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        let upload_id = xml_value(&body, "UploadId").ok_or("Missing UploadId in response")?;
+
+        let result = self.upload_parts(file, key, &upload_id).await;
+        if result.is_err() {
+            let _ = self.send(Method::DELETE, key, &[("uploadId", &upload_id)], Vec::new()).await;
+        }
+        result
+    }
+
+    async fn upload_parts(&self, file: &mut tokio::fs::File, key: &str, upload_id: &str) -> Result<(), String> {
+        let mut etags = Vec::new();
+        loop {
+            let part = read_part(file).await.map_err(|e| e.to_string())?;
+            if part.is_empty() {
+                break;
+            }
+            let number = (etags.len() + 1).to_string();
+            let response = self.send(Method::PUT, key, &[("partNumber", &number), ("uploadId", upload_id)], part).await?;
+            let etag = response.headers().get("etag")
+                .and_then(|v| v.to_str().ok())
+                .ok_or("Missing ETag for uploaded part")?
+                .to_string();
+            etags.push(etag);
+        }
+
+        let parts: String = etags.iter().enumerate()
+            .map(|(i, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{etag}</ETag></Part>", i + 1))
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{parts}</CompleteMultipartUpload>");
+        let response = self.send(Method::POST, key, &[("uploadId", upload_id)], body.into_bytes()).await?;
+
+        // S3 can report a failed completion with a 200 status
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        if text.contains("<Error>") {
+            return Err(xml_value(&text, "Message").unwrap_or(text));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageTarget for S3Target {
+    async fn upload(&self, local_path: &Path, key: &str) -> Result<(), AppError> {
+        let mut file = tokio::fs::File::open(local_path).await.map_err(|e| transfer_error("upload", key, e))?;
+        let size = file.metadata().await.map_err(|e| transfer_error("upload", key, e))?.len();
+
+        let result = if size as usize <= PART_SIZE {
+            let mut body = Vec::with_capacity(size as usize);
+            file.read_to_end(&mut body).await.map_err(|e| transfer_error("upload", key, e))?;
+            self.send(Method::PUT, key, &[], body).await.map(|_| ())
+        } else {
+            self.upload_multipart(&mut file, key).await
+        };
+        result.map_err(|e| transfer_error("upload", key, e))
+    }
+
+    async fn download(&self, key: &str, local_path: &Path) -> Result<(), AppError> {
+        let mut response = self.send(Method::GET, key, &[], Vec::new()).await
+            .map_err(|e| transfer_error("download", key, e))?;
+        let mut file = tokio::fs::File::create(local_path).await.map_err(|e| transfer_error("download", key, e))?;
+
+        while let Some(chunk) = response.chunk().await.map_err(|e| transfer_error("download", key, e))? {
+            file.write_all(&chunk).await.map_err(|e| transfer_error("download", key, e))?;
+        }
+        file.flush().await.map_err(|e| transfer_error("download", key, e))?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match self.send(Method::DELETE, key, &[], Vec::new()).await {
+            Err(e) if !e.starts_with(StatusCode::NOT_FOUND.as_str()) => Err(transfer_error("delete", key, e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encoding as specified by SigV4: everything but unreserved characters (and '/' in paths)
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Text of the first `<tag>` element; S3 responses are small and flat enough for this
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{tag}>"))? + start;
+    Some(xml[start..end].to_string())
+}
+
+/// Read up to `PART_SIZE` bytes; shorter only at end of file
+async fn read_part(file: &mut tokio::fs::File) -> std::io::Result<Vec<u8>> {
+    let mut part = vec![0u8; PART_SIZE];
+    let mut filled = 0;
+    while filled < PART_SIZE {
+        let read = file.read(&mut part[filled..]).await?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    part.truncate(filled);
+    Ok(part)
+}
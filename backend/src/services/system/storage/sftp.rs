@@ -0,0 +1,142 @@
+use std::fs::File;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use axum::async_trait;
+use ssh2::{ErrorCode as SshErrorCode, HashType, RenameFlags, Session, Sftp};
+
+use super::{transfer_error, StorageTarget};
+use crate::core::error::AppError;
+use crate::services::system::backup::safe_relative;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// libssh2 session timeout for every blocking call, in milliseconds
+const SESSION_TIMEOUT_MS: u32 = 60_000;
+/// LIBSSH2_FX_NO_SUCH_FILE
+const SFTP_NO_SUCH_FILE: i32 = 2;
+
+/// SFTP server, authenticated with a password or a private key on the panel host.
+/// libssh2 is blocking, so every operation runs on the blocking pool with its own session.
+#[derive(Clone)]
+pub struct SftpTarget {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: Option<String>,
+    pub private_key_path: Option<String>,
+    /// Hex SHA-256 of the server host key; connections to any other key, or without one, are refused
+    pub host_key_sha256: Option<String>,
+    pub root: String,
+}
+
+impl SftpTarget {
+    fn connect(&self) -> Result<Sftp, String> {
+        let addr = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or_else(|| format!("Could not resolve {}", self.host))?;
+        let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+
+        let mut session = Session::new().map_err(|e| e.to_string())?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(SESSION_TIMEOUT_MS);
+        session.handshake().map_err(|e| e.to_string())?;
+
+        // Checked before any credential is sent; destinations saved without a fingerprint are refused
+        let actual = session.host_key_hash(HashType::Sha256).map(hex::encode).unwrap_or_default();
+        match &self.host_key_sha256 {
+            Some(expected) if actual.eq_ignore_ascii_case(expected.trim()) => {}
+            Some(_) => return Err(format!("Host key mismatch (server presented {actual})")),
+            None => return Err(format!("No host key fingerprint configured (server presented {actual})")),
+        }
+
+        match &self.private_key_path {
+            Some(key) => session.userauth_pubkey_file(&self.username, None, Path::new(key), self.password.as_deref()),
+            None => session.userauth_password(&self.username, self.password.as_deref().unwrap_or_default()),
+        }
+        .map_err(|e| format!("Authentication failed: {e}"))?;
+
+        session.sftp().map_err(|e| e.to_string())
+    }
+
+    fn remote_path(&self, key: &str) -> Result<PathBuf, AppError> {
+        Ok(Path::new(&self.root).join(safe_relative(key)?))
+    }
+
+    /// Run a blocking SFTP operation on its own connection
+    async fn run<T, F>(&self, action: &'static str, key: &str, op: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Sftp) -> Result<T, String> + Send + 'static,
+    {
+        let target = self.clone();
+        tokio::task::spawn_blocking(move || op(&target.connect()?))
+            .await
+            .map_err(|e| AppError::Internal(format!("Spawn blocking failed: {e}")))?
+            .map_err(|e| transfer_error(action, key, e))
+    }
+}
+
+/// `mkdir -p`; existing directories are expected, real failures surface on the next write
+fn create_dirs(sftp: &Sftp, dir: &Path) {
+    let mut current = PathBuf::new();
+    for component in dir.components() {
+        current.push(component);
+        if sftp.stat(&current).is_err() {
+            let _ = sftp.mkdir(&current, 0o755);
+        }
+    }
+}
+
+fn is_missing(e: &ssh2::Error) -> bool {
+    matches!(e.code(), SshErrorCode::SFTP(SFTP_NO_SUCH_FILE))
+}
+
+#[async_trait]
+impl StorageTarget for SftpTarget {
+    async fn upload(&self, local_path: &Path, key: &str) -> Result<(), AppError> {
+        let remote = self.remote_path(key)?;
+        let local_path = local_path.to_path_buf();
+
+        self.run("upload", key, move |sftp| {
+            if let Some(parent) = remote.parent() {
+                create_dirs(sftp, parent);
+            }
+            let tmp = remote.with_extension("part");
+            let mut source = File::open(&local_path).map_err(|e| e.to_string())?;
+            let mut dest = sftp.create(&tmp).map_err(|e| e.to_string())?;
+            io::copy(&mut source, &mut dest).map_err(|e| e.to_string())?;
+            drop(dest);
+
+            sftp.rename(&tmp, &remote, Some(RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE))
+                .map_err(|e| e.to_string())
+        })
+        .await
+    }
+
+    async fn download(&self, key: &str, local_path: &Path) -> Result<(), AppError> {
+        let remote = self.remote_path(key)?;
+        let local_path = local_path.to_path_buf();
+
+        self.run("download", key, move |sftp| {
+            let mut source = sftp.open(&remote).map_err(|e| e.to_string())?;
+            let mut dest = File::create(&local_path).map_err(|e| e.to_string())?;
+            io::copy(&mut source, &mut dest).map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let remote = self.remote_path(key)?;
+
+        self.run("delete", key, move |sftp| match sftp.unlink(&remote) {
+            Err(e) if !is_missing(&e) => Err(e.to_string()),
+            _ => Ok(()),
+        })
+        .await
+    }
+}
@@ -15,18 +15,24 @@ interface Role {
 
 const ALL_PERMISSIONS = [
     { id: "server.view", label: "Voir les serveurs", group: "Serveur" },
+    { id: "server.create", label: "Créer un serveur", group: "Serveur" },
+    { id: "server.edit", label: "Modifier la configuration", group: "Serveur" },
+    { id: "server.delete", label: "Supprimer un serveur", group: "Serveur" },
     { id: "server.start", label: "Démarrer", group: "Serveur" },
     { id: "server.stop", label: "Arrêter", group: "Serveur" },
     { id: "server.restart", label: "Redémarrer", group: "Serveur" },
     { id: "server.kill", label: "Forcer l'arrêt", group: "Serveur" },
+    { id: "server.reinstall", label: "Réinstaller", group: "Serveur" },
     { id: "server.console.read", label: "Lire la console", group: "Console" },
     { id: "server.console.write", label: "Envoyer des commandes", group: "Console" },
     { id: "server.files.read", label: "Voir les fichiers", group: "Fichiers" },
     { id: "server.files.write", label: "Modifier les fichiers", group: "Fichiers" },
     { id: "server.files.delete", label: "Supprimer des fichiers", group: "Fichiers" },
+    { id: "server.players.manage", label: "Gérer les joueurs (whitelist, bans, ops)", group: "Joueurs" },
     { id: "server.backups.view", label: "Voir les sauvegardes", group: "Sauvegardes" },
     { id: "server.backups.create", label: "Créer une sauvegarde", group: "Sauvegardes" },
     { id: "server.backups.restore", label: "Restaurer une sauvegarde", group: "Sauvegardes" },
+    { id: "server.backups.delete", label: "Supprimer une sauvegarde", group: "Sauvegardes" },
     { id: "server.schedules.manage", label: "Gérer les tâches planifiées", group: "Tâches" },
     { id: "users.manage", label: "Gérer les utilisateurs", group: "Administration" },
     { id: "roles.manage", label: "Gérer les rôles", group: "Administration" },
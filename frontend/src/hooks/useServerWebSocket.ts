@@ -67,7 +67,8 @@ export function useServerWebSocket({
     const shouldReconnectRef = useRef(true);
     const serverStatusRef = useRef(serverStatus);
 
-    // Fetch console log history
+    // Fetch the last console log, shown while no console socket is open: once connected, the
+    // socket replays the recent lines itself
     const fetchConsoleLog = useCallback(async () => {
         if (!serverId) return;
         
@@ -77,6 +78,7 @@ export function useServerWebSocket({
             
             // Try console log
             const res = await apiService.readFile(serverId, "logs/console.log");
+            if (wsRef.current?.readyState === WebSocket.OPEN) return;
 
             if (res.success && res.data.content && res.data.content.length > 0) {
                 setLogs(res.data.content.split("\n"));
@@ -112,7 +114,8 @@ export function useServerWebSocket({
         ws.onopen = () => {
             setIsConnected(true);
             retryCountRef.current = 0;
-            fetchConsoleLog();
+            // The server replays its recent console lines on every new connection
+            setLogs([]);
         };
 
         ws.onmessage = (event) => {
@@ -202,7 +205,7 @@ export function useServerWebSocket({
 
         ws.onerror = (err) => console.error("WebSocket error:", err);
         wsRef.current = ws;
-    }, [serverId, fetchConsoleLog, onServerUpdate, onStatusChange]);
+    }, [serverId, onServerUpdate, onStatusChange]);
 
     // Update server status ref and handle connection
     useEffect(() => {
@@ -235,13 +238,6 @@ export function useServerWebSocket({
         }
     }, [serverStatus, connectWebSocket]);
 
-    // Refetch logs when status changes (without reconnecting WS if already active)
-    useEffect(() => {
-        if (serverStatus === "running" || serverStatus === "installing") {
-            fetchConsoleLog();
-        }
-    }, [serverStatus, fetchConsoleLog]);
-
     // Initial connection and cleanup
     useEffect(() => {
         setLogs([]);
@@ -126,7 +126,8 @@ export default function CreateServer() {
         fetch("/api/v1/settings", {
             headers: { Authorization: `Bearer ${localStorage.getItem("token")}` }
         })
-            .then(res => res.json())
+            // Only readable with the settings permission; the form keeps its defaults otherwise
+            .then(res => res.ok ? res.json() : Promise.reject(new Error(`HTTP ${res.status}`)))
             .then(data => {
                 setIsDocker(data.is_docker);
                 setDefaultServersDir(data.servers_dir);
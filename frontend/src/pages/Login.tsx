@@ -49,7 +49,7 @@ export default function Login() {
 
   const fetchLoginSettings = async () => {
     try {
-      const response = await fetch("/api/v1/settings");
+      const response = await fetch("/api/v1/settings/login");
       if (response.ok) {
         const data = await response.json();
         setLoginSettings({
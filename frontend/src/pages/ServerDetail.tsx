@@ -1070,12 +1070,12 @@ export default function ServerDetail() {
                         )}
                         {hasPermission("server.restart") && (
                             <Tooltip content={t("servers.restart")} position="bottom">
-                                <button className="btn btn--sm btn--secondary" onClick={() => handleAction("restart")} disabled={server.status !== "running" && server.status !== "auth_required"}><RotateCw size={16} /></button>
+                                <button className="btn btn--sm btn--secondary" onClick={() => handleAction("restart")} disabled={server.status !== "running" && server.status !== "starting" && server.status !== "auth_required"}><RotateCw size={16} /></button>
                             </Tooltip>
                         )}
                         {hasPermission("server.stop") && (
                             <Tooltip content={t("servers.stop")} position="bottom">
-                                <button className="btn btn--sm btn--danger" onClick={() => handleAction("stop")} disabled={server.status !== "running" && server.status !== "auth_required"}><Square size={16} /></button>
+                                <button className="btn btn--sm btn--danger" onClick={() => handleAction("stop")} disabled={server.status !== "running" && server.status !== "starting" && server.status !== "auth_required"}><Square size={16} /></button>
                             </Tooltip>
                         )}
                     </div>
@@ -177,6 +177,7 @@ JWT_SECRET=$(openssl rand -base64 32)
 SERVERS_DIR=$DATA_DIR/servers
 BACKUPS_DIR=$DATA_DIR/backups
 UPLOADS_DIR=$DATA_DIR/data/uploads
+RUNTIMES_DIR=$DATA_DIR/data/runtimes
 FRONTEND_URL=$PROTOCOL://$IP:5500
 RUST_LOG=info
 EOF
@@ -204,6 +205,11 @@ EnvironmentFile=$DATA_DIR/.env
 ExecStart=$INSTALL_DIR/backend/target/release/draveur
 Restart=always
 RestartSec=10
+# Game servers run detached and are reattached after a restart: only stop the panel itself
+KillMode=process
+# Each server gets a cgroup of its own below the panel's for its resource limits
+Delegate=yes
+DelegateSubgroup=panel
 
 [Install]
 WantedBy=multi-user.target
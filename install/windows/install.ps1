@@ -136,6 +136,7 @@ JWT_SECRET=$jwtSecret
 SERVERS_DIR=$DataDir\servers
 BACKUPS_DIR=$DataDir\backups
 UPLOADS_DIR=$DataDir\data\uploads
+RUNTIMES_DIR=$DataDir\data\runtimes
 FRONTEND_URL=http://$ip:5500
 RUST_LOG=info
 "@ | Set-Content "$DataDir\.env"